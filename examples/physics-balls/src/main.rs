@@ -9,12 +9,16 @@ const GROUND_SIZE: isize = 20;
 const WALL_SIZE: isize = 5;
 const CAMERA_SPEED: f32 = 10.0;
 
-const NR_BALL_TYPES: usize = 3;
-const BALLS: [char; NR_BALL_TYPES] = ['0', 'O', '*'];
+// Terminals without emoji draw these with the glyph fallback
+const NR_BALL_TYPES: usize = 7;
+const BALLS: [char; NR_BALL_TYPES] = ['🔴', '🔵', '🟢', '🟡', '🟠', '🟣', '🟤'];
 
-// Uncomment to use emojis
-// const NR_BALL_TYPES: usize = 7;
-// const BALLS: [char; NR_BALL_TYPES] = ['🔴', '🔵', '🟢', '🟡', '🟠', '🟣', '🟤'];
+const BALL_COLORS: [Color; 4] = [
+    Color::srgb(1.0, 0.3, 0.3),
+    Color::srgb(0.3, 1.0, 0.3),
+    Color::srgb(0.3, 0.5, 1.0),
+    Color::srgb(1.0, 1.0, 0.3),
+];
 
 #[derive(Component)]
 pub struct Ball;
@@ -56,59 +60,44 @@ fn create_scene(mut commands: Commands) {
     });
 
     // Create ground
-    commands
-        .spawn((
-            RigidBody::Static,
-            <SharedShape as Into<Collider>>::into(SharedShape::cuboid(GROUND_SIZE as f32, 1.0)),
-            Transform::from_xyz(0.0, 0.0, 1.0),
-        ))
-        .with_children(|p| {
-            for i in -GROUND_SIZE..=GROUND_SIZE {
-                p.spawn(TermSpriteBundle {
-                    transform: Transform::from_xyz(i as f32, 0.0, 0.0),
-                    char: TermChar('-'),
-                });
-            }
-        });
+    commands.spawn((
+        RigidBody::Static,
+        <SharedShape as Into<Collider>>::into(SharedShape::cuboid(GROUND_SIZE as f32, 1.0)),
+        TermLineBundle {
+            line: TermLine {
+                from: Vec3::new(-GROUND_SIZE as f32, 0.0, 0.0),
+                to: Vec3::new(GROUND_SIZE as f32, 0.0, 0.0),
+                glyph: Some('-'),
+            },
+            transform: Transform::from_xyz(0.0, 0.0, 1.0),
+        },
+    ));
 
     // Create walls
     for i in [-GROUND_SIZE, GROUND_SIZE] {
-        commands
-            .spawn((
-                RigidBody::Static,
-                <SharedShape as Into<Collider>>::into(SharedShape::cuboid(1.0, WALL_SIZE as f32)),
-                Transform::from_xyz(i as f32, WALL_SIZE as f32, 1.0),
-            ))
-            .with_children(|p| {
-                for i in -WALL_SIZE..=WALL_SIZE {
-                    p.spawn(TermSpriteBundle {
-                        transform: Transform::from_xyz(0.0, i as f32, 0.0),
-                        char: TermChar('|'),
-                    });
-                }
-            });
+        commands.spawn((
+            RigidBody::Static,
+            <SharedShape as Into<Collider>>::into(SharedShape::cuboid(1.0, WALL_SIZE as f32)),
+            TermLineBundle {
+                line: TermLine {
+                    from: Vec3::new(0.0, -WALL_SIZE as f32, 0.0),
+                    to: Vec3::new(0.0, WALL_SIZE as f32, 0.0),
+                    glyph: Some('|'),
+                },
+                transform: Transform::from_xyz(i as f32, WALL_SIZE as f32, 1.0),
+            },
+        ));
     }
 
     // Create text
-    commands
-        .spawn(Transform::from_xyz(0.0, -1.0, 0.0))
-        .with_children(|p| {
-            p.spawn(TermTextBundle {
-                text: TermText::from("        Move camera: ↑ ↓ ← →"),
-                transform: Transform::from_xyz(0.0, 0.0, 0.0),
-                ..Default::default()
-            });
-            p.spawn(TermTextBundle {
-                text: TermText::from("Spawn balls: spacebar"),
-                transform: Transform::from_xyz(0.0, -1.0, 0.0),
-                ..Default::default()
-            });
-            p.spawn(TermTextBundle {
-                text: TermText::from(" Exit: q"),
-                transform: Transform::from_xyz(0.0, -2.0, 0.0),
-                ..Default::default()
-            });
-        });
+    commands.spawn((
+        TermTextBundle {
+            text: TermText::from("Move camera: ↑ ↓ ← →\nSpawn balls: spacebar\nExit: q"),
+            transform: Transform::from_xyz(0.0, -1.0, 0.0),
+            ..Default::default()
+        },
+        TermAnchor::TopCenter,
+    ));
 }
 
 fn spawn_balls(mut input: EventReader<TermInput>, mut commands: Commands) {
@@ -119,15 +108,20 @@ fn spawn_balls(mut input: EventReader<TermInput>, mut commands: Commands) {
             let rx = (rng.gen_range(-GROUND_SIZE..=GROUND_SIZE) / 2) as f32;
             let ry = ((WALL_SIZE * 3) + rng.gen_range(0..=WALL_SIZE)) as f32;
             let btype = BALLS[rng.gen_range(0..NR_BALL_TYPES)];
+            let bcolor = BALL_COLORS[rng.gen_range(0..BALL_COLORS.len())];
 
             commands
                 .spawn(Ball)
                 .insert(RigidBody::Dynamic)
                 .insert(Collider::circle(1.0))
                 .insert(Restitution::new(1.1))
-                .insert(TermSpriteBundle {
+                .insert(TermShapeBundle {
+                    shape: TermShape::Circle { radius: 1.0 },
+                    style: TermShapeStyle {
+                        fill: Some(TermCell::from(btype).with_fg(bcolor)),
+                        stroke: None,
+                    },
                     transform: Transform::from_xyz(rx, ry, 0.0),
-                    char: TermChar(btype),
                 });
         }
     }
@@ -158,10 +152,10 @@ fn camera_control(
                 camera.translation += Vec3::X * CAMERA_SPEED;
             }
             TermInput::Up => {
-                camera.translation += Vec3::Y * CAMERA_SPEED / 2.0;
+                camera.translation += Vec3::Y * CAMERA_SPEED;
             }
             TermInput::Down => {
-                camera.translation -= Vec3::Y * CAMERA_SPEED / 2.0;
+                camera.translation -= Vec3::Y * CAMERA_SPEED;
             }
             _ => {}
         }
@@ -57,79 +57,52 @@ fn create_scene(mut commands: Commands) {
     );
 
     // Create text
-    commands
-        .spawn(Transform::from_xyz(0.0, -(DIAMOND_SIZE + 3) as f32, 0.0))
-        .with_children(|p| {
-            p.spawn(TermTextBundle {
-                text: TermText::from("Rotate on x-axis: ↑ ↓"),
-                transform: Transform::from_xyz(0.0, 0.0, 0.0),
-                ..Default::default()
-            });
-            p.spawn(TermTextBundle {
-                text: TermText::from("      Exit: q"),
-                transform: Transform::from_xyz(0.0, -1.0, 0.0),
-                ..Default::default()
-            });
-        });
+    commands.spawn((
+        TermTextBundle {
+            text: TermText::from("Rotate on x-axis: ↑ ↓\nExit: q"),
+            transform: Transform::from_xyz(0.0, -(DIAMOND_SIZE + 3) as f32, 0.0),
+            ..Default::default()
+        },
+        TermAnchor::TopCenter,
+    ));
 }
 
 fn create_diamond(commands: &mut Commands, transform: Transform) {
+    let size = DIAMOND_SIZE as f32;
+    let top = Vec3::new(0.0, size, 0.0);
+    let bottom = Vec3::new(0.0, -size, 0.0);
+    let left = Vec3::new(-size, 0.0, 0.0);
+    let right = Vec3::new(size, 0.0, 0.0);
+    let front = Vec3::new(0.0, 0.0, size);
+    let back = Vec3::new(0.0, 0.0, -size);
+
     commands
         .spawn(DiamondBundle {
             diamond: Diamond,
             transform,
         })
         .with_children(|p| {
-            for i in 0..DIAMOND_SIZE {
-                p.spawn(TermSpriteBundle {
-                    transform: Transform::from_xyz(i as f32, (DIAMOND_SIZE - i) as f32, 0.0),
-                    char: TermChar(DIAMOND_CHAR),
-                });
-                p.spawn(TermSpriteBundle {
-                    transform: Transform::from_xyz((-DIAMOND_SIZE + i) as f32, i as f32, 0.0),
-                    char: TermChar(DIAMOND_CHAR),
-                });
-                p.spawn(TermSpriteBundle {
-                    transform: Transform::from_xyz(-i as f32, -(DIAMOND_SIZE - i) as f32, 0.0),
-                    char: TermChar(DIAMOND_CHAR),
-                });
-                p.spawn(TermSpriteBundle {
-                    transform: Transform::from_xyz(-(-DIAMOND_SIZE + i) as f32, -i as f32, 0.0),
-                    char: TermChar(DIAMOND_CHAR),
-                });
-
-                p.spawn(TermSpriteBundle {
-                    transform: Transform::from_xyz(0.0, (DIAMOND_SIZE - i) as f32, i as f32),
-                    char: TermChar(DIAMOND_CHAR),
-                });
-                p.spawn(TermSpriteBundle {
-                    transform: Transform::from_xyz(0.0, i as f32, (-DIAMOND_SIZE + i) as f32),
-                    char: TermChar(DIAMOND_CHAR),
-                });
-                p.spawn(TermSpriteBundle {
-                    transform: Transform::from_xyz(0.0, -(DIAMOND_SIZE - i) as f32, -i as f32),
-                    char: TermChar(DIAMOND_CHAR),
-                });
-                p.spawn(TermSpriteBundle {
-                    transform: Transform::from_xyz(0.0, -i as f32, -(-DIAMOND_SIZE + i) as f32),
-                    char: TermChar(DIAMOND_CHAR),
-                });
-
-                p.spawn(TermSpriteBundle {
-                    transform: Transform::from_xyz((DIAMOND_SIZE - i) as f32, 0.0, i as f32),
-                    char: TermChar(DIAMOND_CHAR),
-                });
-                p.spawn(TermSpriteBundle {
-                    transform: Transform::from_xyz(i as f32, 0.0, (-DIAMOND_SIZE + i) as f32),
-                    char: TermChar(DIAMOND_CHAR),
-                });
-                p.spawn(TermSpriteBundle {
-                    transform: Transform::from_xyz(-(DIAMOND_SIZE - i) as f32, 0.0, -i as f32),
-                    char: TermChar(DIAMOND_CHAR),
-                });
-                p.spawn(TermSpriteBundle {
-                    transform: Transform::from_xyz(-i as f32, 0.0, -(-DIAMOND_SIZE + i) as f32),
-                    char: TermChar(DIAMOND_CHAR),
+            for (from, to) in [
+                (top, right),
+                (left, top),
+                (bottom, left),
+                (right, bottom),
+                (top, front),
+                (back, top),
+                (bottom, back),
+                (front, bottom),
+                (right, front),
+                (back, right),
+                (left, back),
+                (front, left),
+            ] {
+                p.spawn(TermLineBundle {
+                    line: TermLine {
+                        from,
+                        to,
+                        glyph: Some(DIAMOND_CHAR),
+                    },
+                    ..Default::default()
                 });
             }
         });
@@ -0,0 +1,186 @@
+use bevy::prelude::*;
+
+use crate::prelude::{
+    TermAnimation, TermAnimationFinished, TermAnimationMode, TermArt, TermChar, TermFrames,
+    TermText, TermTextBounds, TermTextEffect, TermTextEffectFinished, TermTextEffectKind,
+};
+use crate::text::{graphemes, layout};
+
+impl TermFrames {
+    fn len(&self) -> usize {
+        match self {
+            TermFrames::Glyphs(frames) => frames.len(),
+            TermFrames::Art(frames) => frames.len(),
+        }
+    }
+}
+
+impl TermAnimation {
+    pub fn new(frames: TermFrames, fps: f32, mode: TermAnimationMode) -> Self {
+        Self {
+            frames,
+            fps,
+            mode,
+            elapsed: 0.0,
+            finished: false,
+        }
+    }
+
+    /// Start the animation over from the first frame
+    pub fn restart(&mut self) {
+        self.elapsed = 0.0;
+        self.finished = false;
+    }
+
+    /// Index of the frame that is showing
+    pub fn frame(&self) -> usize {
+        let len = self.frames.len();
+        if len == 0 {
+            return 0;
+        }
+
+        let step = (self.elapsed * self.fps).max(0.0) as usize;
+        match self.mode {
+            TermAnimationMode::Loop => step % len,
+            TermAnimationMode::PingPong if len > 1 => {
+                // Go back and forth without showing the first and last frames twice
+                let period = 2 * len - 2;
+                let step = step % period;
+                if step < len {
+                    step
+                } else {
+                    period - step
+                }
+            }
+            TermAnimationMode::PingPong => 0,
+            TermAnimationMode::Once => step.min(len - 1),
+        }
+    }
+
+    pub fn finished(&self) -> bool {
+        self.finished
+    }
+
+    pub(crate) fn tick(&mut self, delta: f32) -> bool {
+        self.elapsed += delta;
+
+        // A one shot animation is done when its last frame has been shown for a full frame
+        let done = self.mode == TermAnimationMode::Once
+            && self.elapsed * self.fps >= self.frames.len() as f32;
+        let just_finished = done && !self.finished;
+        self.finished |= done;
+        just_finished
+    }
+}
+
+impl TermTextEffect {
+    pub fn new(kind: TermTextEffectKind) -> Self {
+        Self {
+            kind,
+            elapsed: 0.0,
+            finished: false,
+        }
+    }
+
+    /// Start the effect over, for example to reveal new text
+    pub fn restart(&mut self) {
+        self.elapsed = 0.0;
+        self.finished = false;
+    }
+
+    pub fn elapsed(&self) -> f32 {
+        self.elapsed
+    }
+
+    /// Only a typewriter effect finishes, when all of the text is revealed
+    pub fn finished(&self) -> bool {
+        self.finished
+    }
+
+    pub(crate) fn tick(&mut self, delta: f32) {
+        self.elapsed += delta;
+    }
+
+    pub(crate) fn finish(&mut self) -> bool {
+        let just_finished = !self.finished;
+        self.finished = true;
+        just_finished
+    }
+}
+
+pub(crate) struct AnimationPlugin {}
+
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<TermAnimationFinished>()
+            .add_event::<TermTextEffectFinished>()
+            .add_systems(Update, (animate, animate_text));
+    }
+}
+
+fn animate(
+    time: Res<Time>,
+    mut ev_finished: EventWriter<TermAnimationFinished>,
+    mut animations: Query<(
+        Entity,
+        &mut TermAnimation,
+        Option<&mut TermChar>,
+        Option<&mut TermArt>,
+    )>,
+) {
+    for (entity, mut animation, char, art) in animations.iter_mut() {
+        if animation.finished() {
+            continue;
+        }
+
+        if animation.tick(time.delta_secs()) {
+            ev_finished.write(TermAnimationFinished(entity));
+        }
+
+        let frame = animation.frame();
+        match (&animation.frames, char, art) {
+            (TermFrames::Glyphs(frames), Some(mut char), _) => {
+                if let Some(glyph) = frames.get(frame) {
+                    if char.0 != *glyph {
+                        char.0 = *glyph;
+                    }
+                }
+            }
+            (TermFrames::Art(frames), _, Some(mut art)) => {
+                if let Some(frame) = frames.get(frame) {
+                    if art.art != *frame {
+                        art.art.clone_from(frame);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn animate_text(
+    time: Res<Time>,
+    mut ev_finished: EventWriter<TermTextEffectFinished>,
+    mut effects: Query<(
+        Entity,
+        &mut TermTextEffect,
+        &TermText,
+        Option<&TermTextBounds>,
+    )>,
+) {
+    for (entity, mut effect, text, bounds) in effects.iter_mut() {
+        if effect.finished() {
+            continue;
+        }
+
+        effect.tick(time.delta_secs());
+
+        if let TermTextEffectKind::Typewriter { rate } = effect.kind {
+            // Count what is drawn, wrapping can drop spaces
+            let total = graphemes(&layout(&text.0, bounds));
+            if effect.elapsed() * rate >= total as f32 && effect.finish() {
+                ev_finished.write(TermTextEffectFinished(entity));
+            }
+        }
+    }
+}
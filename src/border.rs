@@ -0,0 +1,251 @@
+use crate::prelude::TermBorderStyle;
+
+const NONE: u8 = 0;
+const LIGHT: u8 = 1;
+const HEAVY: u8 = 2;
+const DOUBLE: u8 = 3;
+
+// Box drawing glyphs and their lines, going up, right, down and left
+const GLYPHS: &[(char, [u8; 4])] = &[
+    ('─', [0, 1, 0, 1]),
+    ('━', [0, 2, 0, 2]),
+    ('│', [1, 0, 1, 0]),
+    ('┃', [2, 0, 2, 0]),
+    ('┌', [0, 1, 1, 0]),
+    ('┍', [0, 2, 1, 0]),
+    ('┎', [0, 1, 2, 0]),
+    ('┏', [0, 2, 2, 0]),
+    ('┐', [0, 0, 1, 1]),
+    ('┑', [0, 0, 1, 2]),
+    ('┒', [0, 0, 2, 1]),
+    ('┓', [0, 0, 2, 2]),
+    ('└', [1, 1, 0, 0]),
+    ('┕', [1, 2, 0, 0]),
+    ('┖', [2, 1, 0, 0]),
+    ('┗', [2, 2, 0, 0]),
+    ('┘', [1, 0, 0, 1]),
+    ('┙', [1, 0, 0, 2]),
+    ('┚', [2, 0, 0, 1]),
+    ('┛', [2, 0, 0, 2]),
+    ('├', [1, 1, 1, 0]),
+    ('┝', [1, 2, 1, 0]),
+    ('┞', [2, 1, 1, 0]),
+    ('┟', [1, 1, 2, 0]),
+    ('┠', [2, 1, 2, 0]),
+    ('┡', [2, 2, 1, 0]),
+    ('┢', [1, 2, 2, 0]),
+    ('┣', [2, 2, 2, 0]),
+    ('┤', [1, 0, 1, 1]),
+    ('┥', [1, 0, 1, 2]),
+    ('┦', [2, 0, 1, 1]),
+    ('┧', [1, 0, 2, 1]),
+    ('┨', [2, 0, 2, 1]),
+    ('┩', [2, 0, 1, 2]),
+    ('┪', [1, 0, 2, 2]),
+    ('┫', [2, 0, 2, 2]),
+    ('┬', [0, 1, 1, 1]),
+    ('┭', [0, 1, 1, 2]),
+    ('┮', [0, 2, 1, 1]),
+    ('┯', [0, 2, 1, 2]),
+    ('┰', [0, 1, 2, 1]),
+    ('┱', [0, 1, 2, 2]),
+    ('┲', [0, 2, 2, 1]),
+    ('┳', [0, 2, 2, 2]),
+    ('┴', [1, 1, 0, 1]),
+    ('┵', [1, 1, 0, 2]),
+    ('┶', [1, 2, 0, 1]),
+    ('┷', [1, 2, 0, 2]),
+    ('┸', [2, 1, 0, 1]),
+    ('┹', [2, 1, 0, 2]),
+    ('┺', [2, 2, 0, 1]),
+    ('┻', [2, 2, 0, 2]),
+    ('┼', [1, 1, 1, 1]),
+    ('┽', [1, 1, 1, 2]),
+    ('┾', [1, 2, 1, 1]),
+    ('┿', [1, 2, 1, 2]),
+    ('╀', [2, 1, 1, 1]),
+    ('╁', [1, 1, 2, 1]),
+    ('╂', [2, 1, 2, 1]),
+    ('╃', [2, 1, 1, 2]),
+    ('╄', [2, 2, 1, 1]),
+    ('╅', [1, 1, 2, 2]),
+    ('╆', [1, 2, 2, 1]),
+    ('╇', [2, 2, 1, 2]),
+    ('╈', [1, 2, 2, 2]),
+    ('╉', [2, 1, 2, 2]),
+    ('╊', [2, 2, 2, 1]),
+    ('╋', [2, 2, 2, 2]),
+    ('═', [0, 3, 0, 3]),
+    ('║', [3, 0, 3, 0]),
+    ('╒', [0, 3, 1, 0]),
+    ('╓', [0, 1, 3, 0]),
+    ('╔', [0, 3, 3, 0]),
+    ('╕', [0, 0, 1, 3]),
+    ('╖', [0, 0, 3, 1]),
+    ('╗', [0, 0, 3, 3]),
+    ('╘', [1, 3, 0, 0]),
+    ('╙', [3, 1, 0, 0]),
+    ('╚', [3, 3, 0, 0]),
+    ('╛', [1, 0, 0, 3]),
+    ('╜', [3, 0, 0, 1]),
+    ('╝', [3, 0, 0, 3]),
+    ('╞', [1, 3, 1, 0]),
+    ('╟', [3, 1, 3, 0]),
+    ('╠', [3, 3, 3, 0]),
+    ('╡', [1, 0, 1, 3]),
+    ('╢', [3, 0, 3, 1]),
+    ('╣', [3, 0, 3, 3]),
+    ('╤', [0, 3, 1, 3]),
+    ('╥', [0, 1, 3, 1]),
+    ('╦', [0, 3, 3, 3]),
+    ('╧', [1, 3, 0, 3]),
+    ('╨', [3, 1, 0, 1]),
+    ('╩', [3, 3, 0, 3]),
+    ('╪', [1, 3, 1, 3]),
+    ('╫', [3, 1, 3, 1]),
+    ('╬', [3, 3, 3, 3]),
+    ('╴', [0, 0, 0, 1]),
+    ('╵', [1, 0, 0, 0]),
+    ('╶', [0, 1, 0, 0]),
+    ('╷', [0, 0, 1, 0]),
+    ('╸', [0, 0, 0, 2]),
+    ('╹', [2, 0, 0, 0]),
+    ('╺', [0, 2, 0, 0]),
+    ('╻', [0, 0, 2, 0]),
+    ('╼', [0, 2, 0, 1]),
+    ('╽', [1, 0, 2, 0]),
+    ('╾', [0, 1, 0, 2]),
+    ('╿', [2, 0, 1, 0]),
+];
+
+const ROUNDED: &[(char, [u8; 4])] = &[
+    ('╭', [0, 1, 1, 0]),
+    ('╮', [0, 0, 1, 1]),
+    ('╯', [1, 0, 0, 1]),
+    ('╰', [1, 1, 0, 0]),
+];
+
+pub(crate) const UP: usize = 0;
+pub(crate) const RIGHT: usize = 1;
+pub(crate) const DOWN: usize = 2;
+pub(crate) const LEFT: usize = 3;
+
+/// The lines leaving a cell that is part of a border. Overlapping borders are merged so the cell
+/// shows the junction of all of them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Edges {
+    lines: [u8; 4],
+    rounded: bool,
+}
+
+impl Edges {
+    pub(crate) fn new(style: &TermBorderStyle, directions: &[usize]) -> Self {
+        let line = match style {
+            TermBorderStyle::Single | TermBorderStyle::Rounded => LIGHT,
+            TermBorderStyle::Heavy => HEAVY,
+            TermBorderStyle::Double => DOUBLE,
+        };
+
+        let mut lines = [NONE; 4];
+        for direction in directions {
+            lines[*direction] = line;
+        }
+
+        Self {
+            lines,
+            rounded: matches!(style, TermBorderStyle::Rounded),
+        }
+    }
+
+    pub(crate) fn merge(self, other: Self) -> Self {
+        let mut lines = self.lines;
+        for (line, other) in lines.iter_mut().zip(other.lines) {
+            *line = (*line).max(other);
+        }
+
+        Self {
+            lines,
+            rounded: self.rounded && other.rounded,
+        }
+    }
+
+    pub(crate) fn glyph(&self) -> char {
+        if self.rounded {
+            if let Some(glyph) = find(ROUNDED, self.lines) {
+                return glyph;
+            }
+        }
+
+        // Not every mix of line styles has a glyph, so fall back to lighter lines until one does
+        let lighter = |from: u8, to: u8| self.lines.map(|l| if l == from { to } else { l });
+        find(GLYPHS, self.lines)
+            .or_else(|| find(GLYPHS, lighter(HEAVY, LIGHT)))
+            .or_else(|| find(GLYPHS, lighter(DOUBLE, LIGHT)))
+            .unwrap_or(' ')
+    }
+}
+
+fn find(glyphs: &[(char, [u8; 4])], lines: [u8; 4]) -> Option<char> {
+    glyphs
+        .iter()
+        .find(|(_, l)| *l == lines)
+        .map(|(glyph, _)| *glyph)
+}
+
+/// ASCII stand-ins for the box drawing glyphs, for terminals that cannot draw them
+pub(crate) fn ascii_glyphs() -> impl Iterator<Item = (char, char)> {
+    GLYPHS.iter().chain(ROUNDED).map(|(glyph, lines)| {
+        let vertical = lines[UP] != NONE || lines[DOWN] != NONE;
+        let horizontal = lines[LEFT] != NONE || lines[RIGHT] != NONE;
+        let ascii = match (vertical, horizontal) {
+            (true, true) => '+',
+            (true, false) => '|',
+            (false, _) if lines.contains(&DOUBLE) => '=',
+            (false, _) => '-',
+        };
+        (*glyph, ascii)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Glyphs for every set of edges, the bits are up, right, down and left from the lowest
+    fn glyphs(style: TermBorderStyle) -> String {
+        (0..16)
+            .map(|mask: usize| {
+                let directions: Vec<usize> = [UP, RIGHT, DOWN, LEFT]
+                    .into_iter()
+                    .filter(|direction| mask & (1 << direction) != 0)
+                    .collect();
+                Edges::new(&style, &directions).glyph()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn every_edge_mask_has_its_glyph() {
+        assert_eq!(glyphs(TermBorderStyle::Single), " ╵╶└╷│┌├╴┘─┴┐┤┬┼");
+        assert_eq!(glyphs(TermBorderStyle::Rounded), " ╵╶╰╷│╭├╴╯─┴╮┤┬┼");
+        assert_eq!(glyphs(TermBorderStyle::Heavy), " ╹╺┗╻┃┏┣╸┛━┻┓┫┳╋");
+        // There are no double line ends, light ones are used instead
+        assert_eq!(glyphs(TermBorderStyle::Double), " ╵╶╚╷║╔╠╴╝═╩╗╣╦╬");
+    }
+
+    #[test]
+    fn merged_edges_join() {
+        let rounded = Edges::new(&TermBorderStyle::Rounded, &[RIGHT, DOWN]);
+        let single = Edges::new(&TermBorderStyle::Single, &[RIGHT, DOWN]);
+        assert_eq!(rounded.merge(rounded).glyph(), '╭');
+        assert_eq!(rounded.merge(single).glyph(), '┌');
+
+        let heavy = Edges::new(&TermBorderStyle::Heavy, &[LEFT, RIGHT]);
+        let light = Edges::new(&TermBorderStyle::Single, &[UP, DOWN]);
+        assert_eq!(heavy.merge(light).glyph(), '┿');
+
+        // Double and heavy lines never meet in a glyph
+        let double = Edges::new(&TermBorderStyle::Double, &[UP, DOWN]);
+        assert_eq!(double.merge(heavy).glyph(), '╫');
+    }
+}
@@ -0,0 +1,359 @@
+use bevy::prelude::*;
+
+use crate::border::{Edges, DOWN, LEFT, RIGHT, UP};
+use crate::prelude::{TermBorderStyle, TermCell, TermShape};
+use crate::term::TermBuffer;
+use std::sync::Arc;
+use unicode_width::UnicodeWidthStr;
+
+// Ellipses are drawn as polygons with this many sides
+const ELLIPSE_SEGMENTS: usize = 64;
+
+/// Bounds and depth checked drawing into the [`TermBuffer`], in terminal cells
+pub(crate) struct Canvas<'a> {
+    buffer: &'a mut TermBuffer,
+    c: usize,
+    r: usize,
+    /// Hyperlink for the grapheme clusters that are drawn
+    link: Option<Arc<str>>,
+}
+
+impl<'a> Canvas<'a> {
+    pub(crate) fn new(buffer: &'a mut TermBuffer) -> Self {
+        let (c, r) = buffer.size();
+        Self {
+            buffer,
+            c,
+            r,
+            link: None,
+        }
+    }
+
+    pub(crate) fn set_link(&mut self, link: Option<&str>) {
+        self.link = link.map(Arc::from);
+    }
+
+    pub(crate) fn size(&self) -> (usize, usize) {
+        (self.c, self.r)
+    }
+
+    pub(crate) fn put(&mut self, x: isize, y: isize, v: TermCell, z: f32) {
+        if x < 0 || y < 0 {
+            return;
+        }
+
+        let x = x as usize;
+        let y = y as usize;
+
+        if x < self.c && y < self.r {
+            self.buffer.write(x, y, v, z);
+        }
+    }
+
+    /// Draw a grapheme cluster, returns how many columns it takes even when it is not drawn
+    pub(crate) fn put_cluster(
+        &mut self,
+        x: isize,
+        y: isize,
+        cluster: &str,
+        v: TermCell,
+        z: f32,
+    ) -> usize {
+        let width = cluster.width();
+        if x < 0 || y < 0 {
+            return width;
+        }
+
+        let x = x as usize;
+        let y = y as usize;
+
+        if x < self.c && y < self.r {
+            self.buffer
+                .write_cluster(x, y, cluster, v, z, self.link.clone());
+        }
+        width
+    }
+
+    /// Show the terminal cursor at a cell this frame, if the cell is in view
+    pub(crate) fn cursor(&mut self, x: isize, y: isize) {
+        if x >= 0 && y >= 0 && (x as usize) < self.c && (y as usize) < self.r {
+            self.buffer.set_cursor(x as usize, y as usize);
+        }
+    }
+
+    /// Draw the border of a box between two corner cells, joining it with any borders already drawn
+    pub(crate) fn border(
+        &mut self,
+        (x0, y0): (isize, isize),
+        (x1, y1): (isize, isize),
+        style: &TermBorderStyle,
+        cell: TermCell,
+        z: f32,
+    ) {
+        let (x0, x1) = (x0.min(x1), x0.max(x1));
+        let (y0, y1) = (y0.min(y1), y0.max(y1));
+
+        // Only walk the part of the border that is in view
+        let (min_x, max_x) = (x0.max(0), x1.min(self.c as isize - 1));
+        let (min_y, max_y) = (y0.max(0), y1.min(self.r as isize - 1));
+
+        for x in min_x..=max_x {
+            for y in [y0, y1] {
+                let mut directions = Vec::with_capacity(4);
+                if x > x0 {
+                    directions.push(LEFT);
+                }
+                if x < x1 {
+                    directions.push(RIGHT);
+                }
+                if y == y0 && y < y1 {
+                    directions.push(DOWN);
+                }
+                if y == y1 && y > y0 {
+                    directions.push(UP);
+                }
+                self.put_border(x, y, cell, z, Edges::new(style, &directions));
+            }
+        }
+
+        for y in min_y.max(y0 + 1)..=max_y.min(y1 - 1) {
+            for x in [x0, x1] {
+                self.put_border(x, y, cell, z, Edges::new(style, &[UP, DOWN]));
+            }
+        }
+    }
+
+    fn put_border(&mut self, x: isize, y: isize, v: TermCell, z: f32, edges: Edges) {
+        if x < 0 || y < 0 {
+            return;
+        }
+
+        let x = x as usize;
+        let y = y as usize;
+
+        if x < self.c && y < self.r {
+            self.buffer.write_border(x, y, v, z, edges);
+        }
+    }
+
+    /// Draw a line between two positions in cells with Bresenham, interpolating depth along the
+    /// way. If no glyph is given one is picked from the slope of the line, as it looks on screen.
+    pub(crate) fn line(&mut self, from: Vec3, to: Vec3, glyph: Option<TermCell>, aspect: f32) {
+        let Some((from, to)) = self.clip(from, to) else {
+            // This line is not in view
+            return;
+        };
+
+        let (x0, y0) = (from.x.floor() as isize, from.y.floor() as isize);
+        let (x1, y1) = (to.x.floor() as isize, to.y.floor() as isize);
+        let glyph = glyph.unwrap_or_else(|| slope_glyph(to - from, aspect).into());
+
+        let dx = (x1 - x0).abs();
+        let dy = -(y1 - y0).abs();
+        let sx = if x0 < x1 { 1 } else { -1 };
+        let sy = if y0 < y1 { 1 } else { -1 };
+        let steps = dx.max(-dy).max(1) as f32;
+
+        let (mut x, mut y) = (x0, y0);
+        let mut err = dx + dy;
+        loop {
+            let t = ((x - x0).abs().max((y - y0).abs())) as f32 / steps;
+            self.put(x, y, glyph, from.z + (to.z - from.z) * t);
+
+            if x == x1 && y == y1 {
+                break;
+            }
+
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+        }
+    }
+
+    /// Draw a closed polygon given in cells. The outline is drawn before the fill, so it wins where
+    /// they overlap at the same depth.
+    pub(crate) fn polygon(
+        &mut self,
+        points: &[Vec3],
+        fill: Option<TermCell>,
+        stroke: Option<TermCell>,
+        aspect: f32,
+    ) {
+        if points.is_empty() {
+            return;
+        }
+
+        if let Some(stroke) = stroke {
+            for (i, from) in points.iter().enumerate() {
+                let to = points[(i + 1) % points.len()];
+                self.line(*from, to, Some(stroke), aspect);
+            }
+        }
+
+        if let Some(fill) = fill {
+            self.fill(points, fill);
+        }
+    }
+
+    /// Fill a polygon given in cells with even-odd scanlines through the cell centers
+    fn fill(&mut self, points: &[Vec3], cell: TermCell) {
+        let z = points.iter().map(|p| p.z).sum::<f32>() / points.len() as f32;
+        let min_y = points
+            .iter()
+            .map(|p| p.y)
+            .fold(f32::MAX, f32::min)
+            .floor()
+            .max(0.0);
+        let max_y = points
+            .iter()
+            .map(|p| p.y)
+            .fold(f32::MIN, f32::max)
+            .ceil()
+            .min(self.r as f32);
+
+        let mut crossings = Vec::new();
+        for y in min_y as isize..max_y as isize {
+            let center = y as f32 + 0.5;
+
+            crossings.clear();
+            for (i, a) in points.iter().enumerate() {
+                let b = points[(i + 1) % points.len()];
+                if (a.y <= center) != (b.y <= center) {
+                    crossings.push(a.x + (center - a.y) / (b.y - a.y) * (b.x - a.x));
+                }
+            }
+            crossings.sort_by(f32::total_cmp);
+
+            for span in crossings.chunks_exact(2) {
+                let from = (span[0] - 0.5).ceil().max(0.0) as isize;
+                let to = (span[1] - 0.5).ceil().min(self.c as f32) as isize;
+                for x in from..to {
+                    self.put(x, y, cell, z);
+                }
+            }
+        }
+    }
+
+    /// Visit every sample point inside a triangle given in cells, with `sub` samples per cell on
+    /// each axis. Samples are given in sub-cell coordinates along with the interpolated depth, only
+    /// samples inside of the terminal are visited.
+    #[cfg(feature = "mesh")]
+    pub(crate) fn triangle(
+        &self,
+        [a, b, c]: [Vec3; 3],
+        (sx, sy): (usize, usize),
+        mut sample: impl FnMut(isize, isize, f32),
+    ) {
+        let scale = Vec3::new(sx as f32, sy as f32, 1.0);
+        let (a, b, c) = (a * scale, b * scale, c * scale);
+
+        let edge =
+            |p: Vec3, q: Vec3, x: f32, y: f32| (q.x - p.x) * (y - p.y) - (q.y - p.y) * (x - p.x);
+        let area = edge(a, b, c.x, c.y);
+        if area == 0.0 {
+            return;
+        }
+
+        let min_x = a.x.min(b.x).min(c.x).floor().max(0.0) as isize;
+        let max_x = a.x.max(b.x).max(c.x).ceil().min((self.c * sx) as f32) as isize;
+        let min_y = a.y.min(b.y).min(c.y).floor().max(0.0) as isize;
+        let max_y = a.y.max(b.y).max(c.y).ceil().min((self.r * sy) as f32) as isize;
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+
+                // Dividing by the area makes the weights positive inside, whatever the winding
+                let wa = edge(b, c, px, py) / area;
+                let wb = edge(c, a, px, py) / area;
+                let wc = edge(a, b, px, py) / area;
+
+                if wa >= 0.0 && wb >= 0.0 && wc >= 0.0 {
+                    sample(x, y, a.z * wa + b.z * wb + c.z * wc);
+                }
+            }
+        }
+    }
+
+    /// Clip a line to the terminal with Liang-Barsky, so lines far out of view are cheap
+    fn clip(&self, from: Vec3, to: Vec3) -> Option<(Vec3, Vec3)> {
+        let d = to - from;
+        let (mut t0, mut t1) = (0.0f32, 1.0f32);
+
+        for (p, q) in [
+            (-d.x, from.x),
+            (d.x, self.c as f32 - from.x),
+            (-d.y, from.y),
+            (d.y, self.r as f32 - from.y),
+        ] {
+            if p == 0.0 {
+                if q < 0.0 {
+                    return None;
+                }
+                continue;
+            }
+
+            let t = q / p;
+            if p < 0.0 {
+                t0 = t0.max(t);
+            } else {
+                t1 = t1.min(t);
+            }
+            if t0 > t1 {
+                return None;
+            }
+        }
+
+        Some((from + d * t0, from + d * t1))
+    }
+}
+
+fn slope_glyph(d: Vec3, aspect: f32) -> char {
+    // Rows are taller than columns are wide, so compare the slope as it looks on screen
+    let dx = d.x.abs() * aspect;
+    let dy = d.y.abs();
+
+    if dy <= dx * (std::f32::consts::PI / 8.0).tan() {
+        '-'
+    } else if dx <= dy * (std::f32::consts::PI / 8.0).tan() {
+        '|'
+    } else if (d.x > 0.0) == (d.y > 0.0) {
+        // Rows grow downwards
+        '\\'
+    } else {
+        '/'
+    }
+}
+
+/// Outline of a shape in its local space
+pub(crate) fn shape_outline(shape: &TermShape) -> Vec<Vec2> {
+    match shape {
+        TermShape::Rect { size } => {
+            let half = *size / 2.0;
+            vec![
+                Vec2::new(-half.x, -half.y),
+                Vec2::new(half.x, -half.y),
+                Vec2::new(half.x, half.y),
+                Vec2::new(-half.x, half.y),
+            ]
+        }
+        TermShape::Circle { radius } => ellipse_outline(Vec2::splat(*radius)),
+        TermShape::Ellipse { radii } => ellipse_outline(*radii),
+        TermShape::Polygon { points } => points.clone(),
+    }
+}
+
+fn ellipse_outline(radii: Vec2) -> Vec<Vec2> {
+    (0..ELLIPSE_SEGMENTS)
+        .map(|i| {
+            let angle = i as f32 / ELLIPSE_SEGMENTS as f32 * std::f32::consts::TAU;
+            Vec2::new(angle.cos(), angle.sin()) * radii
+        })
+        .collect()
+}
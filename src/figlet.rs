@@ -0,0 +1,229 @@
+use bevy::platform::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+
+use crate::prelude::TermFont;
+
+impl TermFont {
+    /// The standard FIGlet font, it is bundled so it is always available
+    pub fn standard() -> Arc<Self> {
+        static STANDARD: OnceLock<Arc<TermFont>> = OnceLock::new();
+        STANDARD
+            .get_or_init(|| {
+                let font = Self::parse(include_str!("fonts/standard.flf"));
+                Arc::new(font.expect("bundled font is valid"))
+            })
+            .clone()
+    }
+
+    pub fn parse(source: &str) -> std::io::Result<Self> {
+        parse(source).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+}
+
+// Every font has these chars in order after the comments, code tagged chars follow them
+const REQUIRED: [u32; 102] = {
+    let mut codes = [0; 102];
+    let mut i = 0;
+    while i < 95 {
+        codes[i] = 32 + i as u32;
+        i += 1;
+    }
+
+    let deutsch = [196, 214, 220, 228, 246, 252, 223];
+    while i < 102 {
+        codes[i] = deutsch[i - 95];
+        i += 1;
+    }
+    codes
+};
+
+pub(crate) fn parse(source: &str) -> Result<TermFont, &'static str> {
+    let mut lines = source.lines();
+    let header = lines.next().ok_or("font is empty")?;
+    let signature = header
+        .strip_prefix("flf2a")
+        .ok_or("missing flf2a signature")?;
+
+    let mut chars = signature.chars();
+    let hardblank = chars.next().ok_or("missing hardblank")?;
+    let params: Vec<i64> = chars
+        .as_str()
+        .split_whitespace()
+        .map(|p| p.parse().map_err(|_| "invalid header"))
+        .collect::<Result<_, _>>()?;
+
+    let [height, _baseline, _max_length, old_layout, comment_lines, ..] = params[..] else {
+        return Err("header is too short");
+    };
+    if height < 1 {
+        return Err("invalid height");
+    }
+    let height = height as usize;
+
+    let mut lines = lines.skip(comment_lines.max(0) as usize);
+    let mut glyphs = HashMap::new();
+    let read_glyph = |lines: &mut dyn Iterator<Item = &str>| -> Option<Vec<Vec<char>>> {
+        let rows: Vec<Vec<char>> = lines.take(height).map(strip_endmark).collect();
+        (rows.len() == height).then_some(rows)
+    };
+
+    for code in REQUIRED {
+        let glyph = read_glyph(&mut lines).ok_or("font ends before all required chars")?;
+        if let Some(char) = char::from_u32(code) {
+            glyphs.insert(char, glyph);
+        }
+    }
+
+    while let Some(tag) = lines.next() {
+        let Some(glyph) = read_glyph(&mut lines) else {
+            break;
+        };
+
+        // Negative codes are not unicode, they are only used by translation tables
+        let code = tag.split_whitespace().next().and_then(parse_code);
+        if let Some(char) = code.and_then(char::from_u32) {
+            glyphs.insert(char, glyph);
+        }
+    }
+
+    Ok(TermFont {
+        height,
+        hardblank,
+        kerning: old_layout >= 0,
+        glyphs,
+    })
+}
+
+/// Codes are decimal, octal with a leading 0, or hex with a leading 0x
+fn parse_code(code: &str) -> Option<u32> {
+    if let Some(hex) = code.strip_prefix("0x").or_else(|| code.strip_prefix("0X")) {
+        u32::from_str_radix(hex, 16).ok()
+    } else if code.len() > 1 && code.starts_with('0') {
+        u32::from_str_radix(&code[1..], 8).ok()
+    } else {
+        code.parse().ok()
+    }
+}
+
+/// Each line ends with an endmark char, the last line of a glyph with two of them
+fn strip_endmark(line: &str) -> Vec<char> {
+    let line = line.trim_end();
+    let Some(endmark) = line.chars().last() else {
+        return Vec::new();
+    };
+
+    line.trim_end_matches(endmark).chars().collect()
+}
+
+/// Render text as rows of big letters, every line of the text becomes `height` rows
+pub(crate) fn render(font: &TermFont, text: &str) -> Vec<String> {
+    let mut rows = Vec::new();
+
+    for line in text.lines() {
+        let mut block = vec![Vec::new(); font.height];
+        for glyph in line.chars().filter_map(|c| font.glyphs.get(&c)) {
+            append(&mut block, glyph, font.kerning);
+        }
+
+        rows.extend(block.into_iter().map(|row| {
+            row.into_iter()
+                .map(|c| if c == font.hardblank { ' ' } else { c })
+                .collect::<String>()
+        }));
+    }
+
+    rows
+}
+
+/// Add a glyph to the right of a block. When kerning, the glyph is moved left until it touches
+/// the block, smushing rules are not applied.
+fn append(block: &mut [Vec<char>], glyph: &[Vec<char>], kerning: bool) {
+    let width = glyph.iter().map(Vec::len).max().unwrap_or(0);
+    let block_width = block.iter().map(Vec::len).max().unwrap_or(0);
+
+    let overlap = if kerning {
+        block
+            .iter()
+            .zip(glyph)
+            .map(|(row, glyph_row)| {
+                let trailing =
+                    block_width - row.len() + row.iter().rev().take_while(|c| **c == ' ').count();
+                let leading = glyph_row.iter().take_while(|c| **c == ' ').count()
+                    + if glyph_row.iter().all(|c| *c == ' ') {
+                        width - glyph_row.len()
+                    } else {
+                        0
+                    };
+                trailing + leading
+            })
+            .min()
+            .unwrap_or(0)
+            .min(width)
+            .min(block_width)
+    } else {
+        0
+    };
+
+    for (row, glyph_row) in block.iter_mut().zip(glyph) {
+        row.resize(block_width, ' ');
+        let start = block_width - overlap;
+        for (i, c) in glyph_row
+            .iter()
+            .chain(std::iter::repeat(&' '))
+            .take(width)
+            .enumerate()
+        {
+            match row.get_mut(start + i) {
+                // Only one of the overlapping chars can be visible, keep that one
+                Some(existing) if *existing == ' ' => *existing = *c,
+                Some(_) => {}
+                None => row.push(*c),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_with_the_standard_font() {
+        let rows = render(&TermFont::standard(), "Hi");
+        assert_eq!(
+            rows,
+            [
+                "  _   _  _ ",
+                " | | | |(_)",
+                " | |_| || |",
+                " |  _  || |",
+                " |_| |_||_|",
+                "           ",
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_fonts() {
+        for source in [
+            "",
+            "not a font",
+            "flf2a",
+            "flf2a$ 6 5",
+            "flf2a$ six 5 16 15 11",
+            "flf2a$ 0 5 16 15 0",
+            // A valid header, but the required chars are missing
+            "flf2a$ 2 1 4 0 0\n @\n @@\n!@\n!@@",
+        ] {
+            assert!(parse(source).is_err(), "{source:?} should not parse");
+        }
+
+        let error = TermFont::parse("flf2a").err().unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,178 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+
+use crate::prelude::{TermCapabilities, TermCommand, TermImage};
+use crate::render::{CameraQuery, RenderContext, TermView};
+use crate::term::{term_commands, term_draw, Term, TermBuffer};
+
+// The kitty graphics protocol does not accept chunks of base64 data larger than this
+const CHUNK_SIZE: usize = 4096;
+
+struct KittyImage {
+    id: u32,
+    placed: Option<(usize, usize)>,
+}
+
+/// Images uploaded to the terminal, they are kept there until their entity is despawned
+#[derive(Resource, Default)]
+struct KittyImages {
+    next_id: u32,
+    images: HashMap<Entity, KittyImage>,
+    size: (usize, usize),
+}
+
+pub(crate) struct GraphicsPlugin {}
+
+impl Plugin for GraphicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KittyImages>().add_systems(
+            Last,
+            (kitty_upload, kitty_place, kitty_delete, kitty_exit)
+                .chain()
+                .after(term_draw)
+                .before(term_commands)
+                .run_if(kitty_enabled),
+        );
+    }
+}
+
+fn kitty_enabled(capabilities: Res<TermCapabilities>) -> bool {
+    capabilities.kitty_graphics
+}
+
+fn kitty_upload(
+    terminal: Res<Term>,
+    mut kitty: ResMut<KittyImages>,
+    images: Query<(Entity, &TermImage), Changed<TermImage>>,
+) {
+    for (entity, image) in images.iter() {
+        let id = match kitty.images.get_mut(&entity) {
+            Some(existing) => {
+                // Uploading with the same id replaces the old data, but placements are lost
+                existing.placed = None;
+                existing.id
+            }
+            None => {
+                kitty.next_id += 1;
+                let id = kitty.next_id;
+                kitty.images.insert(entity, KittyImage { id, placed: None });
+                id
+            }
+        };
+
+        let data = STANDARD.encode(&image.data);
+        let mut out = String::with_capacity(data.len() + 64);
+        let mut chunks = data.as_bytes().chunks(CHUNK_SIZE).peekable();
+        let mut first = true;
+
+        while let Some(chunk) = chunks.next() {
+            let more = chunks.peek().is_some() as u8;
+            // Base64 output is ascii, so any chunk of it is valid utf8
+            let chunk = std::str::from_utf8(chunk).expect("base64 is ascii");
+
+            if first {
+                out.push_str(&format!(
+                    "\x1b_Ga=t,f=32,s={},v={},i={id},q=2,m={more};{chunk}\x1b\\",
+                    image.width, image.height
+                ));
+                first = false;
+            } else {
+                out.push_str(&format!("\x1b_Gm={more};{chunk}\x1b\\"));
+            }
+        }
+
+        terminal.write_raw(out.as_bytes());
+    }
+}
+
+fn kitty_place(
+    context: Res<RenderContext>,
+    terminal: Res<Term>,
+    terminal_buffer: Res<TermBuffer>,
+    mut kitty: ResMut<KittyImages>,
+    camera: CameraQuery,
+    images: Query<(Entity, &GlobalTransform, &TermImage)>,
+) {
+    let (c, r) = terminal_buffer.size();
+    let view = TermView::new(&camera, c, r);
+
+    // Clearing the screen on resize removes all placements
+    if kitty.size != (c, r) {
+        kitty.size = (c, r);
+        for image in kitty.images.values_mut() {
+            image.placed = None;
+        }
+    }
+
+    let mut out = String::new();
+    for (entity, transform, image) in images.iter() {
+        let Some(kitty_image) = kitty.images.get_mut(&entity) else {
+            continue;
+        };
+
+        // Images below minz are hidden like the cells drawn for everything else
+        let cell = match context.visible(transform) {
+            true => view.cell(transform.translation()).map(|(x, y, _)| (x, y)),
+            false => None,
+        };
+        if cell == kitty_image.placed {
+            continue;
+        }
+
+        let id = kitty_image.id;
+        match cell {
+            Some((x, y)) => {
+                // Placing with the same placement id moves the existing placement
+                let size = match image.cells {
+                    Some((columns, rows)) => format!(",c={columns},r={rows}"),
+                    None => String::new(),
+                };
+                out.push_str(&format!(
+                    "\x1b[{};{}H\x1b_Ga=p,i={id},p=1,C=1,q=2{size}\x1b\\",
+                    y + 1,
+                    x + 1
+                ));
+            }
+            None => {
+                // Out of view or hidden, remove the placement but keep the data
+                out.push_str(&format!("\x1b_Ga=d,d=i,i={id},p=1,q=2\x1b\\"));
+            }
+        }
+        kitty_image.placed = cell;
+    }
+
+    if !out.is_empty() {
+        terminal.write_raw(out.as_bytes());
+    }
+}
+
+fn kitty_delete(
+    terminal: Res<Term>,
+    mut kitty: ResMut<KittyImages>,
+    mut removed: RemovedComponents<TermImage>,
+) {
+    let mut out = String::new();
+    for entity in removed.read() {
+        if let Some(image) = kitty.images.remove(&entity) {
+            // Capital I frees the image data as well as the placements
+            out.push_str(&format!("\x1b_Ga=d,d=I,i={},q=2\x1b\\", image.id));
+        }
+    }
+
+    if !out.is_empty() {
+        terminal.write_raw(out.as_bytes());
+    }
+}
+
+fn kitty_exit(
+    terminal: Res<Term>,
+    mut kitty: ResMut<KittyImages>,
+    mut ev_cmd: EventReader<TermCommand>,
+) {
+    if let Some(TermCommand::Exit) = ev_cmd.read().next() {
+        // Do not leave images behind in the terminal
+        kitty.images.clear();
+        terminal.write_raw(b"\x1b_Ga=d,d=A,q=2\x1b\\");
+    }
+}
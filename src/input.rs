@@ -0,0 +1,702 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bevy::prelude::*;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::prelude::{
+    TermCapabilities, TermClipboard, TermCursorStyle, TermInput, TermTextArea, TermTextAreaChanged,
+    TermTextInput, TermTextInputChanged, TermTextInputSubmitted,
+};
+use crate::term::{term_events, term_send_input, TermPendingInput, TermRawOutput};
+
+// Clipboard keys of text areas, Ctrl+X, Ctrl+K and Ctrl+U
+const CUT: char = '\u{18}';
+const COPY: char = '\u{b}';
+const PASTE: char = '\u{15}';
+
+// Tabs are typed as spaces, so every cell keeps one glyph
+const TAB: &str = "    ";
+
+type Position = (usize, usize);
+
+impl TermTextInput {
+    /// Replace the value and move the cursor to its end
+    pub fn set_value(&mut self, value: &str) {
+        self.value = value.to_string();
+        self.cursor = self.value.graphemes(true).count();
+    }
+
+    pub fn with_placeholder(mut self, placeholder: &str) -> Self {
+        self.placeholder = placeholder.to_string();
+        self
+    }
+
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    pub fn with_width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    pub fn with_focus(mut self) -> Self {
+        self.focused = true;
+        self
+    }
+}
+
+impl TermTextArea {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            lines: vec![String::new()],
+            cursor: (0, 0),
+            selection: None,
+            focused: false,
+            width,
+            height,
+            fg: None,
+            cursor_style: TermCursorStyle::Cell {
+                fg: Some(Color::BLACK),
+                bg: Some(Color::WHITE),
+            },
+            selection_fg: Some(Color::BLACK),
+            selection_bg: Some(Color::srgb(0.5, 0.5, 0.5)),
+            scroll: (0, 0),
+        }
+    }
+
+    pub fn value(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    /// Replace the text and move the cursor to its end
+    pub fn set_value(&mut self, value: &str) {
+        self.lines = value.split('\n').map(str::to_string).collect();
+        let last = self.lines.len() - 1;
+        self.cursor = (last, self.lines[last].graphemes(true).count());
+        self.selection = None;
+    }
+
+    pub fn cursor(&self) -> (usize, usize) {
+        self.cursor
+    }
+
+    /// The selected text, if anything is selected
+    pub fn selected(&self) -> Option<String> {
+        selected(self)
+    }
+
+    pub fn with_value(mut self, value: &str) -> Self {
+        self.set_value(value);
+        self
+    }
+
+    pub fn with_focus(mut self) -> Self {
+        self.focused = true;
+        self
+    }
+}
+
+pub(crate) struct InputPlugin {}
+
+impl Plugin for InputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TermClipboard>()
+            .add_event::<TermTextInputChanged>()
+            .add_event::<TermTextInputSubmitted>()
+            .add_event::<TermTextAreaChanged>()
+            .add_systems(
+                PreUpdate,
+                (edit_text_inputs, edit_text_areas)
+                    .chain()
+                    .after(term_events)
+                    .before(term_send_input),
+            );
+    }
+}
+
+fn edit_text_inputs(
+    mut pending: ResMut<TermPendingInput>,
+    mut ev_changed: EventWriter<TermTextInputChanged>,
+    mut ev_submitted: EventWriter<TermTextInputSubmitted>,
+    mut inputs: Query<(Entity, &mut TermTextInput)>,
+) {
+    if pending.0.is_empty() {
+        return;
+    }
+    let events = &pending.0;
+    let mut handled = vec![false; events.len()];
+
+    for (entity, input) in inputs.iter_mut() {
+        if !input.focused {
+            continue;
+        }
+
+        let input = input.into_inner();
+        let mut changed = false;
+        for (ev, handled) in events.iter().zip(handled.iter_mut()) {
+            let len = input.value.graphemes(true).count();
+            input.cursor = input.cursor.min(len);
+
+            match ev {
+                TermInput::Character(c) if is_backspace(*c) => changed |= backspace(input),
+                TermInput::Character(c) if !c.is_control() => {
+                    changed |= insert(input, *c, len);
+                }
+                TermInput::SpaceBar => changed |= insert(input, ' ', len),
+                TermInput::BackSpace => changed |= backspace(input),
+                TermInput::Delete => changed |= remove(&mut input.value, input.cursor),
+                TermInput::Left => input.cursor = input.cursor.saturating_sub(1),
+                TermInput::Right => input.cursor = (input.cursor + 1).min(len),
+                TermInput::Home => input.cursor = 0,
+                TermInput::End => input.cursor = len,
+                TermInput::Enter => {
+                    ev_submitted.write(TermTextInputSubmitted {
+                        entity,
+                        value: input.value.clone(),
+                    });
+                }
+                _ => continue,
+            }
+            *handled = true;
+        }
+
+        if changed {
+            ev_changed.write(TermTextInputChanged {
+                entity,
+                value: input.value.clone(),
+            });
+        }
+
+        input.scroll = scroll_to_cursor(&input.value, input.cursor, input.width, input.scroll);
+    }
+
+    keep_unhandled(&mut pending, &handled);
+}
+
+fn edit_text_areas(
+    capabilities: Res<TermCapabilities>,
+    raw_output: ResMut<TermRawOutput>,
+    mut clipboard: ResMut<TermClipboard>,
+    mut pending: ResMut<TermPendingInput>,
+    mut ev_changed: EventWriter<TermTextAreaChanged>,
+    mut areas: Query<(Entity, &mut TermTextArea)>,
+) {
+    if pending.0.is_empty() {
+        return;
+    }
+    let events = &pending.0;
+    let mut handled = vec![false; events.len()];
+
+    // Only copy to the system clipboard in terminals that take OSC 52
+    let mut raw_output = capabilities.clipboard.then_some(raw_output.into_inner());
+
+    for (entity, area) in areas.iter_mut() {
+        if !area.focused {
+            continue;
+        }
+
+        let area = area.into_inner();
+        let mut changed = false;
+        for (ev, handled) in events.iter().zip(handled.iter_mut()) {
+            clamp(area);
+
+            match ev {
+                TermInput::Character(c) if is_backspace(*c) => changed |= area_backspace(area),
+                TermInput::Character(CUT) => {
+                    if let Some(text) = selected(area) {
+                        copy(raw_output.as_deref_mut(), &mut clipboard, text);
+                        changed |= delete_selection(area);
+                    }
+                }
+                TermInput::Character(COPY) => {
+                    if let Some(text) = selected(area) {
+                        copy(raw_output.as_deref_mut(), &mut clipboard, text);
+                    }
+                }
+                TermInput::Character(PASTE) => changed |= insert_str(area, &clipboard.0),
+                TermInput::Character(c) if !c.is_control() => {
+                    changed |= insert_str(area, c.encode_utf8(&mut [0; 4]));
+                }
+                TermInput::SpaceBar => changed |= insert_str(area, " "),
+                TermInput::Tab => changed |= insert_str(area, TAB),
+                TermInput::Enter => changed |= insert_str(area, "\n"),
+                TermInput::BackSpace => changed |= area_backspace(area),
+                TermInput::Delete => changed |= area_delete(area),
+                TermInput::Left => move_cursor(area, false, left),
+                TermInput::Right => move_cursor(area, false, right),
+                TermInput::Up => move_cursor(area, false, |area| vertical(area, -1)),
+                TermInput::Down => move_cursor(area, false, |area| vertical(area, 1)),
+                TermInput::Home => move_cursor(area, false, home),
+                TermInput::End => move_cursor(area, false, end),
+                TermInput::PageUp => {
+                    let page = area.height.max(1) as isize;
+                    move_cursor(area, false, |area| vertical(area, -page));
+                }
+                TermInput::PageDown => {
+                    let page = area.height.max(1) as isize;
+                    move_cursor(area, false, |area| vertical(area, page));
+                }
+                TermInput::ShiftLeft => move_cursor(area, true, left),
+                TermInput::ShiftRight => move_cursor(area, true, right),
+                TermInput::ShiftUp => move_cursor(area, true, |area| vertical(area, -1)),
+                TermInput::ShiftDown => move_cursor(area, true, |area| vertical(area, 1)),
+                TermInput::ShiftHome => move_cursor(area, true, home),
+                TermInput::ShiftEnd => move_cursor(area, true, end),
+                _ => continue,
+            }
+            *handled = true;
+        }
+
+        if changed {
+            ev_changed.write(TermTextAreaChanged(entity));
+        }
+
+        let (line, column) = area.cursor;
+        let height = area.height.max(1);
+        if line < area.scroll.0 {
+            area.scroll.0 = line;
+        } else if line >= area.scroll.0 + height {
+            area.scroll.0 = line + 1 - height;
+        }
+        area.scroll.1 = scroll_to_cursor(&area.lines[line], column, area.width, area.scroll.1);
+    }
+
+    keep_unhandled(&mut pending, &handled);
+}
+
+/// Take the keys a focused field used out of the pending input, so they are never sent as events
+fn keep_unhandled(pending: &mut TermPendingInput, handled: &[bool]) {
+    let mut handled = handled.iter();
+    pending
+        .0
+        .retain(|_| !handled.next().copied().unwrap_or(false));
+}
+
+/// Keep the text area valid after its fields were changed from outside
+fn clamp(area: &mut TermTextArea) {
+    if area.lines.is_empty() {
+        area.lines.push(String::new());
+    }
+
+    let clamp = |lines: &[String], (line, column): Position| {
+        let line = line.min(lines.len() - 1);
+        (line, column.min(lines[line].graphemes(true).count()))
+    };
+    area.cursor = clamp(&area.lines, area.cursor);
+    area.selection = area.selection.map(|s| clamp(&area.lines, s));
+}
+
+/// The selection from its first to its last position, if it is not empty
+pub(crate) fn selection_range(area: &TermTextArea) -> Option<(Position, Position)> {
+    let selection = area.selection?;
+    match selection.cmp(&area.cursor) {
+        std::cmp::Ordering::Less => Some((selection, area.cursor)),
+        std::cmp::Ordering::Greater => Some((area.cursor, selection)),
+        std::cmp::Ordering::Equal => None,
+    }
+}
+
+pub(crate) fn selected(area: &TermTextArea) -> Option<String> {
+    let (start, end) = selection_range(area)?;
+    let lines = &area.lines;
+    let from = byte_index(&lines[start.0], start.1);
+    let to = byte_index(&lines[end.0], end.1);
+
+    if start.0 == end.0 {
+        return Some(lines[start.0][from..to].to_string());
+    }
+
+    let mut text = lines[start.0][from..].to_string();
+    for line in &lines[start.0 + 1..end.0] {
+        text.push('\n');
+        text.push_str(line);
+    }
+    text.push('\n');
+    text.push_str(&lines[end.0][..to]);
+    Some(text)
+}
+
+/// Keep the text, and send it to the system clipboard with OSC 52 when there is an output for it
+fn copy(raw_output: Option<&mut TermRawOutput>, clipboard: &mut TermClipboard, text: String) {
+    if let Some(raw_output) = raw_output {
+        raw_output.0 += &format!("\x1b]52;c;{}\x07", STANDARD.encode(&text));
+    }
+    clipboard.0 = text;
+}
+
+fn delete_selection(area: &mut TermTextArea) -> bool {
+    let Some((start, end)) = selection_range(area) else {
+        area.selection = None;
+        return false;
+    };
+
+    let to = byte_index(&area.lines[end.0], end.1);
+    let tail = area.lines[end.0][to..].to_string();
+    let line = &mut area.lines[start.0];
+    line.truncate(byte_index(line, start.1));
+    line.push_str(&tail);
+    area.lines.drain(start.0 + 1..=end.0);
+
+    area.cursor = start;
+    area.selection = None;
+    true
+}
+
+/// Type text at the cursor, replacing the selection
+fn insert_str(area: &mut TermTextArea, text: &str) -> bool {
+    delete_selection(area);
+    if text.is_empty() {
+        return false;
+    }
+
+    let (line, column) = area.cursor;
+    let at = byte_index(&area.lines[line], column);
+    let tail = area.lines[line].split_off(at);
+
+    let mut parts = text.split('\n');
+    if let Some(first) = parts.next() {
+        area.lines[line].push_str(first);
+    }
+
+    let mut last = line;
+    for part in parts {
+        last += 1;
+        area.lines.insert(last, part.to_string());
+    }
+
+    // A combining mark joins the cluster before it instead of adding one
+    let end = area.lines[last].len();
+    area.lines[last].push_str(&tail);
+    area.cursor = (last, area.lines[last][..end].graphemes(true).count());
+    true
+}
+
+fn area_backspace(area: &mut TermTextArea) -> bool {
+    if delete_selection(area) {
+        return true;
+    }
+
+    let (line, column) = area.cursor;
+    if column > 0 {
+        area.cursor.1 -= 1;
+        remove(&mut area.lines[line], column - 1)
+    } else if line > 0 {
+        area.cursor = line_end(area, line - 1);
+        let joined = area.lines.remove(line);
+        area.lines[line - 1].push_str(&joined);
+        true
+    } else {
+        false
+    }
+}
+
+fn area_delete(area: &mut TermTextArea) -> bool {
+    if delete_selection(area) {
+        return true;
+    }
+
+    let (line, column) = area.cursor;
+    if remove(&mut area.lines[line], column) {
+        true
+    } else if line + 1 < area.lines.len() {
+        let joined = area.lines.remove(line + 1);
+        area.lines[line].push_str(&joined);
+        true
+    } else {
+        false
+    }
+}
+
+/// Move the cursor, extending the selection or dropping it
+fn move_cursor(area: &mut TermTextArea, select: bool, to: impl Fn(&TermTextArea) -> Position) {
+    if !select {
+        area.selection = None;
+    } else if area.selection.is_none() {
+        area.selection = Some(area.cursor);
+    }
+
+    area.cursor = to(area);
+}
+
+fn left(area: &TermTextArea) -> Position {
+    match area.cursor {
+        (line, 0) if line > 0 => line_end(area, line - 1),
+        (line, column) => (line, column.saturating_sub(1)),
+    }
+}
+
+fn right(area: &TermTextArea) -> Position {
+    let (line, column) = area.cursor;
+    if column < area.lines[line].graphemes(true).count() {
+        (line, column + 1)
+    } else if line + 1 < area.lines.len() {
+        (line + 1, 0)
+    } else {
+        (line, column)
+    }
+}
+
+fn home(area: &TermTextArea) -> Position {
+    (area.cursor.0, 0)
+}
+
+fn end(area: &TermTextArea) -> Position {
+    line_end(area, area.cursor.0)
+}
+
+fn line_end(area: &TermTextArea, line: usize) -> Position {
+    (line, area.lines[line].graphemes(true).count())
+}
+
+/// Move up or down by a number of lines, staying in the same column as well as the line allows
+fn vertical(area: &TermTextArea, lines: isize) -> Position {
+    let (line, column) = area.cursor;
+    let target = line as isize + lines;
+    if target < 0 {
+        return (0, 0);
+    }
+    if target as usize >= area.lines.len() {
+        return line_end(area, area.lines.len() - 1);
+    }
+
+    let target = target as usize;
+    let x = cursor_column(&area.lines[line], column);
+    let mut width = 0;
+    let column = area.lines[target]
+        .graphemes(true)
+        .take_while(|g| {
+            width += g.width();
+            width <= x
+        })
+        .count();
+    (target, column)
+}
+
+/// Terminals send either of these for backspace when curses does not know the key
+fn is_backspace(c: char) -> bool {
+    c == '\u{7f}' || c == '\u{8}'
+}
+
+fn insert(input: &mut TermTextInput, c: char, len: usize) -> bool {
+    if input.max_length.is_some_and(|max| len >= max) {
+        return false;
+    }
+
+    let at = byte_index(&input.value, input.cursor);
+    input.value.insert(at, c);
+
+    // A combining mark joins the cluster before it instead of adding one
+    let end = at + c.len_utf8();
+    input.cursor = input.value[..end].graphemes(true).count();
+    true
+}
+
+fn backspace(input: &mut TermTextInput) -> bool {
+    if input.cursor == 0 {
+        return false;
+    }
+
+    input.cursor -= 1;
+    remove(&mut input.value, input.cursor)
+}
+
+/// Remove the grapheme cluster at an index, returns whether there was one
+fn remove(value: &mut String, index: usize) -> bool {
+    let Some((at, grapheme)) = value.grapheme_indices(true).nth(index) else {
+        return false;
+    };
+
+    let end = at + grapheme.len();
+    value.replace_range(at..end, "");
+    true
+}
+
+/// Byte index of a grapheme cluster, or the end of the string if it is past the last one
+fn byte_index(value: &str, index: usize) -> usize {
+    value
+        .grapheme_indices(true)
+        .nth(index)
+        .map_or(value.len(), |(at, _)| at)
+}
+
+/// Column the cursor is drawn at, before the grapheme cluster it is on
+pub(crate) fn cursor_column(value: &str, cursor: usize) -> usize {
+    value[..byte_index(value, cursor)].width()
+}
+
+/// Scroll in columns that keeps the cursor in view, along with the cell it takes at the end of the
+/// value
+pub(crate) fn scroll_to_cursor(value: &str, cursor: usize, width: usize, scroll: usize) -> usize {
+    let column = cursor_column(value, cursor);
+    if column < scroll {
+        column
+    } else if column >= scroll + width {
+        column + 1 - width.max(1)
+    } else {
+        scroll
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::term::TermPendingInput;
+
+    // Keys seen by a game system in Update, as debug strings
+    #[derive(Resource, Default)]
+    struct ReadKeys(Vec<String>);
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_event::<TermInput>()
+            .insert_resource(TermCapabilities {
+                kitty_graphics: false,
+                hyperlinks: false,
+                clipboard: false,
+                unicode: true,
+            })
+            .init_resource::<TermRawOutput>()
+            .init_resource::<TermPendingInput>()
+            .init_resource::<ReadKeys>()
+            .add_plugins(InputPlugin {})
+            .add_systems(PreUpdate, term_send_input)
+            .add_systems(
+                Update,
+                |mut ev_input: EventReader<TermInput>, mut read: ResMut<ReadKeys>| {
+                    read.0.extend(ev_input.read().map(|ev| format!("{ev:?}")));
+                },
+            );
+        app
+    }
+
+    fn press(app: &mut App, keys: impl IntoIterator<Item = TermInput>) {
+        app.world_mut()
+            .resource_mut::<TermPendingInput>()
+            .0
+            .extend(keys);
+    }
+
+    fn area(value: &str, cursor: Position) -> TermTextArea {
+        let mut area = TermTextArea::new(40, 10).with_value(value);
+        area.cursor = cursor;
+        area
+    }
+
+    #[test]
+    fn edits_whole_grapheme_clusters() {
+        // A combining accent and a wide glyph are one cluster each
+        let mut text = area("cafe\u{301} 日本", (0, 3));
+        move_cursor(&mut text, false, right);
+        assert_eq!(text.cursor, (0, 4));
+        assert!(area_backspace(&mut text));
+        assert_eq!(text.value(), "caf 日本");
+
+        let mut text = area("cafe\u{301} 日本", (0, 5));
+        assert!(area_delete(&mut text));
+        assert_eq!(text.value(), "cafe\u{301} 本");
+
+        insert_str(&mut text, "語");
+        assert_eq!(text.value(), "cafe\u{301} 語本");
+        assert_eq!(text.cursor, (0, 6));
+        assert_eq!(cursor_column(&text.lines[0], text.cursor.1), 7);
+    }
+
+    #[test]
+    fn deletes_a_backward_selection() {
+        let mut text = area("first line\nsecond line", (1, 6));
+        move_cursor(&mut text, true, |area| vertical(area, -1));
+        move_cursor(&mut text, true, left);
+        assert_eq!(text.cursor, (0, 5));
+        assert_eq!(text.selected().as_deref(), Some(" line\nsecond"));
+
+        assert!(area_backspace(&mut text));
+        assert_eq!(text.value(), "first line");
+        assert_eq!(text.cursor, (0, 5));
+        assert_eq!(text.selection, None);
+    }
+
+    #[test]
+    fn moves_vertically_onto_shorter_lines() {
+        let mut text = area("a long line\nshort\n\nanother long line", (0, 9));
+        move_cursor(&mut text, false, |area| vertical(area, 1));
+        assert_eq!(text.cursor, (1, 5));
+        move_cursor(&mut text, false, |area| vertical(area, 1));
+        assert_eq!(text.cursor, (2, 0));
+
+        // The display column is kept, so a wide glyph is not split
+        let mut text = area("日本語\nabcdef", (1, 3));
+        move_cursor(&mut text, false, |area| vertical(area, -1));
+        assert_eq!(text.cursor, (0, 1));
+    }
+
+    #[test]
+    fn inserts_a_newline_mid_line() {
+        let mut text = area("hello world", (0, 5));
+        assert!(insert_str(&mut text, "\n"));
+        assert_eq!(text.lines, ["hello", " world"]);
+        assert_eq!(text.cursor, (1, 0));
+
+        assert!(area_backspace(&mut text));
+        assert_eq!(text.value(), "hello world");
+        assert_eq!(text.cursor, (0, 5));
+    }
+
+    #[test]
+    fn unused_keys_are_read_once() {
+        let mut app = app();
+        press(&mut app, [TermInput::Character('q')]);
+        for _ in 0..6 {
+            app.update();
+        }
+
+        assert_eq!(app.world().resource::<ReadKeys>().0, ["Character('q')"]);
+    }
+
+    #[test]
+    fn focused_fields_keep_the_keys_they_use() {
+        let mut app = app();
+        let input = app
+            .world_mut()
+            .spawn(TermTextInput::default().with_focus())
+            .id();
+        press(
+            &mut app,
+            [TermInput::Character('q'), TermInput::Up, TermInput::Escape],
+        );
+        for _ in 0..6 {
+            app.update();
+        }
+
+        assert_eq!(app.world().get::<TermTextInput>(input).unwrap().value, "q");
+        assert_eq!(app.world().resource::<ReadKeys>().0, ["Up", "Escape"]);
+    }
+
+    #[test]
+    fn focused_areas_keep_the_keys_they_use() {
+        let mut app = app();
+        let area = app
+            .world_mut()
+            .spawn(TermTextArea::default().with_focus())
+            .id();
+        press(
+            &mut app,
+            [
+                TermInput::Character('a'),
+                TermInput::Enter,
+                TermInput::Up,
+                TermInput::Escape,
+            ],
+        );
+        for _ in 0..6 {
+            app.update();
+        }
+
+        let area = app.world().get::<TermTextArea>(area).unwrap();
+        assert_eq!(area.value(), "a\n");
+        assert_eq!(area.cursor(), (0, 0));
+        assert_eq!(app.world().resource::<ReadKeys>().0, ["Escape"]);
+    }
+}
@@ -1,4 +1,14 @@
+mod animation;
+mod border;
+mod draw;
+mod figlet;
+mod graphics;
+mod input;
+#[cfg(feature = "mesh")]
+mod mesh;
+mod particles;
 mod plugin;
 pub mod prelude;
 mod render;
 mod term;
+mod text;
@@ -0,0 +1,249 @@
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy::render::mesh::{Mesh2d, Mesh3d, PrimitiveTopology, VertexAttributeValues};
+use bevy::sprite::{ColorMaterial, MeshMaterial2d};
+
+use crate::draw::Canvas;
+use crate::prelude::{
+    TermCell, TermLight, TermMeshFill, TermMeshMode, TermMeshResolution, TermMeshShading,
+};
+use crate::render::{render_borders, render_shapes, CameraQuery, RenderContext, TermView};
+use crate::term::TermBuffer;
+
+type Mesh2dQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static GlobalTransform,
+        &'static Mesh2d,
+        Option<&'static MeshMaterial2d<ColorMaterial>>,
+        Option<&'static TermMeshFill>,
+    ),
+>;
+
+pub(crate) struct MeshPlugin {}
+
+impl Plugin for MeshPlugin {
+    fn build(&self, app: &mut App) {
+        // Meshes are drawn with the other shapes, so borders still merge on top of them
+        app.add_systems(
+            PostUpdate,
+            (render_meshes_2d, render_meshes_3d)
+                .chain()
+                .after(render_shapes)
+                .before(render_borders),
+        );
+    }
+}
+
+/// Triangles of a mesh in its local space, if it is a triangle list with positions
+pub(crate) fn mesh_triangles(mesh: &Mesh) -> Option<Vec<[Vec3; 3]>> {
+    if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+        return None;
+    }
+
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        return None;
+    };
+
+    let indices: Vec<usize> = match mesh.indices() {
+        Some(indices) => indices.iter().collect(),
+        None => (0..positions.len()).collect(),
+    };
+
+    Some(
+        indices
+            .chunks_exact(3)
+            .filter_map(|t| {
+                Some([
+                    Vec3::from(*positions.get(t[0])?),
+                    Vec3::from(*positions.get(t[1])?),
+                    Vec3::from(*positions.get(t[2])?),
+                ])
+            })
+            .collect(),
+    )
+}
+
+fn render_meshes_2d(
+    context: Res<RenderContext>,
+    terminal_buffer: ResMut<TermBuffer>,
+    camera: CameraQuery,
+    meshes: Option<Res<Assets<Mesh>>>,
+    materials: Option<Res<Assets<ColorMaterial>>>,
+    entities: Mesh2dQuery,
+) {
+    let Some(meshes) = meshes else {
+        // Mesh assets are not registered, so there is nothing to draw
+        return;
+    };
+
+    let terminal_buffer = terminal_buffer.into_inner();
+    let (c, r) = terminal_buffer.size();
+    let view = TermView::new(&camera, c, r);
+    let mut canvas = Canvas::new(terminal_buffer);
+    let default_fill = TermMeshFill::default();
+
+    // Samples covered by the current mesh, per cell
+    let mut cells: HashMap<(isize, isize), (u8, f32)> = HashMap::default();
+
+    for (transform, mesh, material, fill) in entities
+        .iter()
+        .filter(|(transform, ..)| context.visible(transform))
+    {
+        let Some(triangles) = meshes.get(&mesh.0).and_then(mesh_triangles) else {
+            continue;
+        };
+
+        let fill = fill.unwrap_or(&default_fill);
+        let fg = material
+            .zip(materials.as_ref())
+            .and_then(|(material, materials)| materials.get(&material.0))
+            .map(|material| material.color);
+        let (sx, sy) = match fill.resolution {
+            TermMeshResolution::Cell => (1, 1),
+            TermMeshResolution::HalfBlock => (1, 2),
+            TermMeshResolution::Braille => (2, 4),
+        };
+
+        cells.clear();
+        for triangle in triangles {
+            // Cutting a triangle at the near plane can leave four corners, or none
+            let corners = view.project_polygon(triangle.map(|p| transform.transform_point(p)));
+            for i in 1..corners.len().saturating_sub(1) {
+                canvas.triangle(
+                    [corners[0], corners[i], corners[i + 1]],
+                    (sx, sy),
+                    |x, y, z| {
+                        let (dx, dy) = ((x % sx as isize) as u8, (y % sy as isize) as u8);
+                        let cell = cells
+                            .entry((x / sx as isize, y / sy as isize))
+                            .or_insert((0, f32::MIN));
+                        cell.0 |= sample_bit(fill.resolution, dx, dy);
+                        cell.1 = cell.1.max(z);
+                    },
+                );
+            }
+        }
+
+        for ((x, y), (samples, z)) in cells.iter() {
+            let glyph = match fill.resolution {
+                TermMeshResolution::Cell => fill.glyph,
+                TermMeshResolution::HalfBlock => [' ', '▀', '▄', '█'][*samples as usize],
+                TermMeshResolution::Braille => char::from_u32(0x2800 + *samples as u32)
+                    .expect("braille patterns cover every byte"),
+            };
+
+            canvas.put(
+                *x,
+                *y,
+                TermCell {
+                    glyph,
+                    fg,
+                    bg: None,
+                },
+                *z,
+            );
+        }
+    }
+}
+
+fn sample_bit(resolution: TermMeshResolution, dx: u8, dy: u8) -> u8 {
+    match resolution {
+        TermMeshResolution::Cell => 1,
+        TermMeshResolution::HalfBlock => 1 << dy,
+        // Braille dots are numbered down the left column, then the right, with the bottom row last
+        TermMeshResolution::Braille => match dy {
+            3 => 1 << (6 + dx),
+            _ => 1 << (dy + 3 * dx),
+        },
+    }
+}
+
+fn render_meshes_3d(
+    context: Res<RenderContext>,
+    terminal_buffer: ResMut<TermBuffer>,
+    camera: CameraQuery,
+    meshes: Option<Res<Assets<Mesh>>>,
+    lights: Query<&GlobalTransform, With<TermLight>>,
+    entities: Query<(&GlobalTransform, &Mesh3d, Option<&TermMeshShading>)>,
+) {
+    let Some(meshes) = meshes else {
+        // Mesh assets are not registered, so there is nothing to draw
+        return;
+    };
+
+    let terminal_buffer = terminal_buffer.into_inner();
+    let (c, r) = terminal_buffer.size();
+    let view = TermView::new(&camera, c, r);
+    let mut canvas = Canvas::new(terminal_buffer);
+    let default_shading = TermMeshShading::default();
+    let mut samples = Vec::new();
+
+    // Direction towards the light
+    let light = -lights
+        .single()
+        .map_or(view.forward(), |light| light.forward().as_vec3());
+
+    for (transform, mesh, shading) in entities
+        .iter()
+        .filter(|(transform, ..)| context.visible(transform))
+    {
+        let Some(triangles) = meshes.get(&mesh.0).and_then(mesh_triangles) else {
+            continue;
+        };
+
+        let shading = shading.unwrap_or(&default_shading);
+        let ramp: Vec<char> = shading.ramp.chars().collect();
+
+        for triangle in triangles {
+            let world = triangle.map(|p| transform.transform_point(p));
+
+            match shading.mode {
+                TermMeshMode::Wireframe => {
+                    for i in 0..3 {
+                        if let Some((from, to)) = view.project_segment(world[i], world[(i + 1) % 3])
+                        {
+                            canvas.line(from, to, None, view.aspect());
+                        }
+                    }
+                }
+                TermMeshMode::Solid => {
+                    // Cutting a triangle at the near plane can leave four corners, or none
+                    let cells = view.project_polygon(world);
+                    if ramp.is_empty() || cells.len() < 3 {
+                        continue;
+                    }
+
+                    // Light both sides of the triangle, whichever faces the camera
+                    let mut normal = (world[1] - world[0])
+                        .cross(world[2] - world[0])
+                        .normalize_or_zero();
+                    if !view.faces_camera(world[0], normal) {
+                        normal = -normal;
+                    }
+
+                    let intensity = normal.dot(light).clamp(0.0, 1.0);
+                    let glyph = ramp[(intensity * (ramp.len() - 1) as f32).round() as usize];
+                    let cell = TermCell {
+                        glyph,
+                        fg: shading.fg,
+                        bg: None,
+                    };
+
+                    samples.clear();
+                    for i in 1..cells.len() - 1 {
+                        canvas.triangle([cells[0], cells[i], cells[i + 1]], (1, 1), |x, y, z| {
+                            samples.push((x, y, z));
+                        });
+                    }
+                    for (x, y, z) in samples.iter() {
+                        canvas.put(*x, *y, cell, *z);
+                    }
+                }
+            }
+        }
+    }
+}
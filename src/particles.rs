@@ -0,0 +1,82 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::prelude::{TermParticleEmitter, TermParticles};
+
+#[derive(Clone, Copy)]
+pub(crate) struct Particle {
+    pub position: Vec3,
+    pub velocity: Vec2,
+    pub age: f32,
+}
+
+impl TermParticles {
+    /// Spawn a number of particles at once on the next update
+    pub fn burst(&mut self, count: usize) {
+        self.burst += count;
+    }
+
+    pub fn len(&self) -> usize {
+        self.particles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+}
+
+pub(crate) struct ParticlePlugin {}
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, simulate_particles);
+    }
+}
+
+fn simulate_particles(
+    time: Res<Time>,
+    mut emitters: Query<(&GlobalTransform, &TermParticleEmitter, &mut TermParticles)>,
+) {
+    let delta = time.delta_secs();
+    let mut rng = rand::thread_rng();
+
+    for (transform, emitter, particles) in emitters.iter_mut() {
+        let particles = particles.into_inner();
+
+        // Age and move the living, dropping the dead in place so the pool keeps its allocation
+        particles.particles.retain_mut(|particle| {
+            particle.age += delta;
+            particle.velocity += emitter.gravity * delta;
+            particle.position += (particle.velocity * delta).extend(0.0);
+            particle.age < emitter.lifetime
+        });
+
+        // Spawn new particles, carrying over fractions of a particle between frames
+        particles.pending += emitter.rate * delta;
+        let count = particles.pending as usize + particles.burst;
+        particles.pending = particles.pending.fract();
+        particles.burst = 0;
+
+        let free = emitter
+            .max_particles
+            .saturating_sub(particles.particles.len());
+        for _ in 0..count.min(free) {
+            let angle = if emitter.spread > 0.0 {
+                rng.gen_range(-emitter.spread..=emitter.spread)
+            } else {
+                0.0
+            };
+            let speed = if emitter.speed_spread > 0.0 {
+                1.0 + rng.gen_range(-emitter.speed_spread..=emitter.speed_spread)
+            } else {
+                1.0
+            };
+
+            particles.particles.push(Particle {
+                position: transform.translation(),
+                velocity: Vec2::from_angle(angle).rotate(emitter.velocity) * speed,
+                age: 0.0,
+            });
+        }
+    }
+}
@@ -1,8 +1,15 @@
+use super::animation::AnimationPlugin;
+use super::graphics::GraphicsPlugin;
+use super::input::InputPlugin;
+#[cfg(feature = "mesh")]
+use super::mesh::MeshPlugin;
+use super::particles::ParticlePlugin;
 use super::render::RenderPlugin;
 use super::term::TermDrawPlugin;
 use bevy::prelude::*;
 
 pub struct TermPlugin {
+    /// Entities at or below this world z are not drawn, wherever the camera is
     pub minz: f32,
 }
 
@@ -14,6 +21,16 @@ impl Default for TermPlugin {
 
 impl Plugin for TermPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins((TermDrawPlugin {}, RenderPlugin { minz: self.minz }));
+        app.add_plugins((
+            TermDrawPlugin {},
+            RenderPlugin { minz: self.minz },
+            GraphicsPlugin {},
+            AnimationPlugin {},
+            ParticlePlugin {},
+            InputPlugin {},
+        ));
+
+        #[cfg(feature = "mesh")]
+        app.add_plugins(MeshPlugin {});
     }
 }
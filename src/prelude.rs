@@ -1,4 +1,7 @@
+use crate::particles::Particle;
+use bevy::platform::collections::HashMap;
 use bevy::prelude::*;
+use std::sync::Arc;
 
 pub use super::plugin::TermPlugin;
 
@@ -25,6 +28,13 @@ pub enum TermInput {
     PageUp,
     PageDown,
 
+    ShiftLeft,
+    ShiftRight,
+    ShiftUp,
+    ShiftDown,
+    ShiftHome,
+    ShiftEnd,
+
     F1,
     F2,
     F3,
@@ -44,9 +54,67 @@ pub enum TermCommand {
     Exit,
 }
 
+/// Sent when a [`TermAnimationMode::Once`] animation has shown its last frame
+#[derive(Debug, Event)]
+pub struct TermAnimationFinished(pub Entity);
+
+/// Sent when a [`TermTextEffectKind::Typewriter`] effect has revealed all of its text
+#[derive(Debug, Event)]
+pub struct TermTextEffectFinished(pub Entity);
+
+/// Sent when the value of a [`TermTextInput`] is edited
+#[derive(Debug, Event)]
+pub struct TermTextInputChanged {
+    pub entity: Entity,
+    pub value: String,
+}
+
+/// Sent when the text of a [`TermTextArea`] is edited
+#[derive(Debug, Event)]
+pub struct TermTextAreaChanged(pub Entity);
+
+/// Sent when enter is pressed in a focused [`TermTextInput`]
+#[derive(Debug, Event)]
+pub struct TermTextInputSubmitted {
+    pub entity: Entity,
+    pub value: String,
+}
+
 #[derive(Component)]
 pub struct TermChar(pub char);
 
+/// A glyph and its colors, colors that are not set use the terminal defaults
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TermCell {
+    pub glyph: char,
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+}
+
+impl TermCell {
+    pub fn with_fg(mut self, color: Color) -> Self {
+        self.fg = Some(color);
+        self
+    }
+
+    pub fn with_bg(mut self, color: Color) -> Self {
+        self.bg = Some(color);
+        self
+    }
+}
+
+impl From<char> for TermCell {
+    fn from(glyph: char) -> Self {
+        Self {
+            glyph,
+            fg: None,
+            bg: None,
+        }
+    }
+}
+
+/// Text drawn from the entity position, a '\n' starts a new line below. Add [`TermTextBounds`] to
+/// wrap it to a box.
 #[derive(Component)]
 pub struct TermText(pub String);
 
@@ -56,6 +124,128 @@ impl From<&str> for TermText {
     }
 }
 
+/// Makes the [`TermText`] or [`TermBigText`] on the same entity a hyperlink, in terminals that can
+/// open them
+#[derive(Component)]
+pub struct TermLink(pub String);
+
+/// A line between two points, relative to the entity transform. If no glyph is given one of
+/// '-', '/', '|' or '\' is picked from the slope of the line.
+#[derive(Component)]
+pub struct TermLine {
+    pub from: Vec3,
+    pub to: Vec3,
+    pub glyph: Option<char>,
+}
+
+/// A shape drawn in world space under the entity transform, rotation included. Sizes are in world
+/// units and shapes are centered on the entity, polygon points are relative to it.
+#[derive(Component, Clone)]
+pub enum TermShape {
+    Rect { size: Vec2 },
+    Circle { radius: f32 },
+    Ellipse { radii: Vec2 },
+    Polygon { points: Vec<Vec2> },
+}
+
+/// How a [`TermShape`] is drawn, leave out the fill for an outline or the stroke for a solid shape
+#[derive(Component)]
+pub struct TermShapeStyle {
+    pub fill: Option<TermCell>,
+    pub stroke: Option<TermCell>,
+}
+
+impl Default for TermShapeStyle {
+    fn default() -> Self {
+        Self {
+            fill: None,
+            stroke: Some('#'.into()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TermBorderStyle {
+    Single,
+    Double,
+    Rounded,
+    Heavy,
+}
+
+/// A box drawn with box drawing characters, centered on the entity. Where borders overlap they are
+/// joined into the matching junction glyph.
+#[derive(Component)]
+pub struct TermBorder {
+    pub size: Vec2,
+    pub style: TermBorderStyle,
+    pub fg: Option<Color>,
+}
+
+/// How many samples are taken per cell when filling meshes
+#[cfg(feature = "mesh")]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TermMeshResolution {
+    /// One sample per cell, filled with the fill glyph
+    Cell,
+    /// Two samples per cell, drawn with half block characters
+    HalfBlock,
+    /// Eight samples per cell, drawn with braille characters
+    Braille,
+}
+
+/// How a bevy `Mesh2d` is filled. Meshes without this are filled with '#' at cell resolution, and
+/// are colored by their `ColorMaterial` if they have one.
+#[cfg(feature = "mesh")]
+#[derive(Component)]
+pub struct TermMeshFill {
+    pub glyph: char,
+    pub resolution: TermMeshResolution,
+}
+
+#[cfg(feature = "mesh")]
+impl Default for TermMeshFill {
+    fn default() -> Self {
+        Self {
+            glyph: '#',
+            resolution: TermMeshResolution::Cell,
+        }
+    }
+}
+
+#[cfg(feature = "mesh")]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TermMeshMode {
+    Solid,
+    Wireframe,
+}
+
+/// How a bevy `Mesh3d` is drawn. Solid meshes pick a glyph from the ramp by how much light hits
+/// each triangle, darkest first. Meshes without this are drawn solid with the default ramp.
+#[cfg(feature = "mesh")]
+#[derive(Component)]
+pub struct TermMeshShading {
+    pub mode: TermMeshMode,
+    pub ramp: String,
+    pub fg: Option<Color>,
+}
+
+#[cfg(feature = "mesh")]
+impl Default for TermMeshShading {
+    fn default() -> Self {
+        Self {
+            mode: TermMeshMode::Solid,
+            ramp: String::from(".,-~:;=!*#$@"),
+            fg: None,
+        }
+    }
+}
+
+/// A directional light shining along the local -z axis of its entity, like bevy lights do. Without
+/// one, meshes are lit from the direction of the [`TermCamera`].
+#[cfg(feature = "mesh")]
+#[derive(Component)]
+pub struct TermLight;
+
 #[derive(Component)]
 pub enum TermTextAlign {
     LEFT,
@@ -63,6 +253,237 @@ pub enum TermTextAlign {
     RIGHT,
 }
 
+/// The point of a block of text or [`TermArt`] that is placed at the entity. Text with an anchor
+/// ignores its [`TermTextAlign`], lines are still aligned with each other on the horizontal part of
+/// the anchor.
+///
+/// The point is on the edge of the block, so like right aligned text, a block anchored on the right
+/// or bottom ends just left of or above the entity.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub enum TermAnchor {
+    TopLeft,
+    TopCenter,
+    TopRight,
+    CenterLeft,
+    Center,
+    CenterRight,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+}
+
+impl From<TermAnchor> for Vec2 {
+    fn from(anchor: TermAnchor) -> Self {
+        match anchor {
+            TermAnchor::TopLeft => Vec2::new(0.0, 0.0),
+            TermAnchor::TopCenter => Vec2::new(0.5, 0.0),
+            TermAnchor::TopRight => Vec2::new(1.0, 0.0),
+            TermAnchor::CenterLeft => Vec2::new(0.0, 0.5),
+            TermAnchor::Center => Vec2::new(0.5, 0.5),
+            TermAnchor::CenterRight => Vec2::new(1.0, 0.5),
+            TermAnchor::BottomLeft => Vec2::new(0.0, 1.0),
+            TermAnchor::BottomCenter => Vec2::new(0.5, 1.0),
+            TermAnchor::BottomRight => Vec2::new(1.0, 1.0),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TermTextWrap {
+    /// Break lines between words, words longer than a line are broken between chars
+    Word,
+    Char,
+    /// Only break lines at newlines, lines that are too long are cut like the overflow says
+    None,
+}
+
+/// What to do with text that does not fit [`TermTextBounds`], lines that are too long are cut the
+/// same way as lines that do not fit the height
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TermTextOverflow {
+    Clip,
+    /// Clip, and end a line with '…' where anything was cut from it or from below it
+    Ellipsis,
+    /// Skip this many lines from the top. It is clamped so the last line stays at the bottom, so
+    /// `Scroll(usize::MAX)` follows the end of a growing log. Lines that are too long are clipped.
+    Scroll(usize),
+}
+
+/// Wraps a [`TermText`] to at most `width` columns and `height` rows
+#[derive(Component, Clone, Copy, Debug)]
+pub struct TermTextBounds {
+    pub width: usize,
+    pub height: usize,
+    pub wrap: TermTextWrap,
+    pub overflow: TermTextOverflow,
+}
+
+impl TermTextBounds {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            wrap: TermTextWrap::Word,
+            overflow: TermTextOverflow::Clip,
+        }
+    }
+
+    pub fn with_wrap(mut self, wrap: TermTextWrap) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    pub fn with_overflow(mut self, overflow: TermTextOverflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TermTextEffectKind {
+    /// Reveal the text one grapheme cluster at a time, `rate` of them per second
+    Typewriter { rate: f32 },
+    /// Scroll each line from right to left through a window `width` columns wide, `speed` columns
+    /// per second
+    Marquee { width: usize, speed: f32 },
+    /// Show the text for half of the period and hide it for the other half
+    Blink { period: f32 },
+    /// Fade the text color from one color to the other and back once per period
+    Pulse { from: Color, to: Color, period: f32 },
+}
+
+/// Changes how the [`TermText`] on the same entity is drawn over time, the text itself is left
+/// alone. Times are in seconds.
+#[derive(Component)]
+pub struct TermTextEffect {
+    pub kind: TermTextEffectKind,
+    pub(crate) elapsed: f32,
+    pub(crate) finished: bool,
+}
+
+/// How the cursor of a text input is shown
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TermCursorStyle {
+    /// The terminal cursor, only one can be shown at a time
+    Hardware,
+    /// The cell under the cursor is drawn with these colors
+    Cell {
+        fg: Option<Color>,
+        bg: Option<Color>,
+    },
+}
+
+/// A single line text field, drawn from the entity to the right. It is edited with the keyboard
+/// while it is focused, and scrolls sideways to keep the cursor in view.
+///
+/// The keys a focused field uses are not sent as [`TermInput`] events, so game systems do not see
+/// what is typed into it.
+#[derive(Component)]
+pub struct TermTextInput {
+    pub value: String,
+    /// Position of the cursor in grapheme clusters
+    pub cursor: usize,
+    pub focused: bool,
+    /// Longest value in grapheme clusters
+    pub max_length: Option<usize>,
+    /// Shown while the value is empty
+    pub placeholder: String,
+    pub placeholder_fg: Option<Color>,
+    pub fg: Option<Color>,
+    /// Columns the field takes
+    pub width: usize,
+    pub cursor_style: TermCursorStyle,
+    pub(crate) scroll: usize,
+}
+
+impl Default for TermTextInput {
+    fn default() -> Self {
+        Self {
+            value: String::new(),
+            cursor: 0,
+            focused: false,
+            max_length: None,
+            placeholder: String::new(),
+            placeholder_fg: Some(Color::srgb(0.5, 0.5, 0.5)),
+            fg: None,
+            width: 20,
+            cursor_style: TermCursorStyle::Cell {
+                fg: Some(Color::BLACK),
+                bg: Some(Color::WHITE),
+            },
+            scroll: 0,
+        }
+    }
+}
+
+#[derive(Bundle)]
+pub struct TermTextInputBundle {
+    pub input: TermTextInput,
+    pub transform: Transform,
+}
+
+impl Default for TermTextInputBundle {
+    fn default() -> Self {
+        Self {
+            input: TermTextInput::default(),
+            transform: Transform::from_xyz(0.0, 0.0, 0.0),
+        }
+    }
+}
+
+/// Text cut or copied from a [`TermTextArea`], and what is pasted into one. Copying also sends the
+/// text to the system clipboard when the terminal has [`TermCapabilities::clipboard`].
+#[derive(Resource, Default)]
+pub struct TermClipboard(pub String);
+
+/// A multi-line text editor in a box of `width` by `height` cells, drawn from the entity to the
+/// right and down. It scrolls to keep the cursor in view while it is focused.
+///
+/// Shift with the arrows, home or end selects text. Ctrl+X cuts the selection, Ctrl+K copies it
+/// and Ctrl+U pastes the [`TermClipboard`], as terminals usually keep Ctrl+C and Ctrl+V to
+/// themselves.
+///
+/// Like with a [`TermTextInput`], the keys a focused area uses are not sent as [`TermInput`]
+/// events.
+#[derive(Component)]
+pub struct TermTextArea {
+    pub(crate) lines: Vec<String>,
+    /// Line and grapheme cluster in the line
+    pub(crate) cursor: (usize, usize),
+    /// Where the selection started, it runs from here to the cursor
+    pub(crate) selection: Option<(usize, usize)>,
+    pub focused: bool,
+    pub width: usize,
+    pub height: usize,
+    pub fg: Option<Color>,
+    pub cursor_style: TermCursorStyle,
+    pub selection_fg: Option<Color>,
+    pub selection_bg: Option<Color>,
+    /// First line and column in view
+    pub(crate) scroll: (usize, usize),
+}
+
+impl Default for TermTextArea {
+    fn default() -> Self {
+        Self::new(40, 10)
+    }
+}
+
+#[derive(Bundle)]
+pub struct TermTextAreaBundle {
+    pub area: TermTextArea,
+    pub transform: Transform,
+}
+
+impl Default for TermTextAreaBundle {
+    fn default() -> Self {
+        Self {
+            area: TermTextArea::default(),
+            transform: Transform::from_xyz(0.0, 0.0, 0.0),
+        }
+    }
+}
+
 #[derive(Bundle)]
 pub struct TermSpriteBundle {
     pub char: TermChar,
@@ -78,6 +499,202 @@ impl Default for TermSpriteBundle {
     }
 }
 
+/// A grid of tiles drawn as one entity, going right and down from its transform. Tiles that are a
+/// space without a background color are see through.
+#[derive(Component, Clone)]
+pub struct TermTilemap {
+    pub width: usize,
+    pub height: usize,
+    /// Tiles row by row, starting at the top
+    pub tiles: Vec<TermCell>,
+    /// Size of a tile in world units, the default covers one cell with the default camera aspect
+    pub tile_size: Vec2,
+}
+
+#[derive(Bundle)]
+pub struct TermTilemapBundle {
+    pub tilemap: TermTilemap,
+    pub transform: Transform,
+}
+
+impl Default for TermTilemapBundle {
+    fn default() -> Self {
+        Self {
+            tilemap: TermTilemap::new(1, 1, '?'.into()),
+            transform: Transform::from_xyz(0.0, 0.0, 0.0),
+        }
+    }
+}
+
+/// A block of text art drawn as one entity, one grapheme cluster per cell. The anchor is the point
+/// of the art that is placed at the entity, from (0, 0) at the top left to (1, 1) at the bottom
+/// right. A [`TermAnchor`] on the same entity is used instead when there is one.
+#[derive(Component, Clone)]
+pub struct TermArt {
+    pub art: String,
+    /// Cells with this char are not drawn, so whatever is behind the art shows through
+    pub transparent: Option<char>,
+    pub anchor: Vec2,
+    pub fg: Option<Color>,
+    /// Per cell colors, a block of the same shape as the art where each char is looked up in the
+    /// palette. Cells that are not found in the palette use `fg`.
+    pub color_mask: Option<String>,
+    pub palette: Vec<(char, Color)>,
+}
+
+impl From<&str> for TermArt {
+    fn from(s: &str) -> Self {
+        Self {
+            art: s.to_string(),
+            transparent: Some(' '),
+            anchor: Vec2::ZERO,
+            fg: None,
+            color_mask: None,
+            palette: Vec::new(),
+        }
+    }
+}
+
+#[derive(Bundle)]
+pub struct TermArtBundle {
+    pub art: TermArt,
+    pub transform: Transform,
+}
+
+impl Default for TermArtBundle {
+    fn default() -> Self {
+        Self {
+            art: TermArt::from("?"),
+            transform: Transform::from_xyz(0.0, 0.0, 0.0),
+        }
+    }
+}
+
+pub enum TermFrames {
+    /// Frames for a [`TermChar`]
+    Glyphs(Vec<char>),
+    /// Frames for a [`TermArt`]
+    Art(Vec<String>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TermAnimationMode {
+    Loop,
+    PingPong,
+    Once,
+}
+
+/// Cycles the [`TermChar`] or [`TermArt`] on the same entity through a list of frames
+#[derive(Component)]
+pub struct TermAnimation {
+    pub frames: TermFrames,
+    pub fps: f32,
+    pub mode: TermAnimationMode,
+    pub(crate) elapsed: f32,
+    pub(crate) finished: bool,
+}
+
+/// Emits particles from the entity position. Particles live in a pool on the entity and are drawn
+/// straight into the frame, they are not entities themselves.
+#[derive(Component)]
+#[require(TermParticles)]
+pub struct TermParticleEmitter {
+    /// Particles per second, set to 0 and use [`TermParticles::burst`] for one off effects
+    pub rate: f32,
+    /// Seconds a particle lives
+    pub lifetime: f32,
+    /// Velocity of new particles in world units per second
+    pub velocity: Vec2,
+    /// New particles have their velocity turned by a random angle, up to this many radians either
+    /// way
+    pub spread: f32,
+    /// New particles have their speed changed by a random fraction up to this much either way
+    pub speed_spread: f32,
+    pub gravity: Vec2,
+    /// How particles look over their lifetime, from birth to death
+    pub gradient: Vec<TermCell>,
+    /// Size of the particle pool, no new particles are spawned while it is full
+    pub max_particles: usize,
+}
+
+impl Default for TermParticleEmitter {
+    fn default() -> Self {
+        Self {
+            rate: 10.0,
+            lifetime: 1.0,
+            velocity: Vec2::new(0.0, 10.0),
+            spread: std::f32::consts::FRAC_PI_4,
+            speed_spread: 0.2,
+            gravity: Vec2::new(0.0, -10.0),
+            gradient: vec!['*'.into(), '+'.into(), '.'.into()],
+            max_particles: 256,
+        }
+    }
+}
+
+/// The particle pool of a [`TermParticleEmitter`]
+#[derive(Component, Default)]
+pub struct TermParticles {
+    pub(crate) particles: Vec<Particle>,
+    pub(crate) pending: f32,
+    pub(crate) burst: usize,
+}
+
+#[derive(Bundle)]
+pub struct TermLineBundle {
+    pub line: TermLine,
+    pub transform: Transform,
+}
+
+impl Default for TermLineBundle {
+    fn default() -> Self {
+        Self {
+            line: TermLine {
+                from: Vec3::ZERO,
+                to: Vec3::X,
+                glyph: None,
+            },
+            transform: Transform::from_xyz(0.0, 0.0, 0.0),
+        }
+    }
+}
+
+#[derive(Bundle)]
+pub struct TermShapeBundle {
+    pub shape: TermShape,
+    pub style: TermShapeStyle,
+    pub transform: Transform,
+}
+
+impl Default for TermShapeBundle {
+    fn default() -> Self {
+        Self {
+            shape: TermShape::Rect { size: Vec2::ONE },
+            style: TermShapeStyle::default(),
+            transform: Transform::from_xyz(0.0, 0.0, 0.0),
+        }
+    }
+}
+
+#[derive(Bundle)]
+pub struct TermBorderBundle {
+    pub border: TermBorder,
+    pub transform: Transform,
+}
+
+impl Default for TermBorderBundle {
+    fn default() -> Self {
+        Self {
+            border: TermBorder {
+                size: Vec2::ONE,
+                style: TermBorderStyle::Single,
+                fg: None,
+            },
+            transform: Transform::from_xyz(0.0, 0.0, 0.0),
+        }
+    }
+}
+
 #[derive(Bundle)]
 pub struct TermTextBundle {
     pub text: TermText,
@@ -95,9 +712,179 @@ impl Default for TermTextBundle {
     }
 }
 
+/// A FIGlet font for [`TermBigText`], loaded from a `.flf` file
+pub struct TermFont {
+    pub(crate) height: usize,
+    pub(crate) hardblank: char,
+    pub(crate) kerning: bool,
+    pub(crate) glyphs: HashMap<char, Vec<Vec<char>>>,
+}
+
+/// Text drawn in big letters made of chars with a FIGlet font. It is aligned like [`TermText`], and
+/// takes a [`TermAnchor`] as well.
+#[derive(Component, Clone)]
+pub struct TermBigText {
+    pub text: String,
+    pub font: Arc<TermFont>,
+    pub fg: Option<Color>,
+}
+
+impl TermBigText {
+    pub fn with_font(mut self, font: Arc<TermFont>) -> Self {
+        self.font = font;
+        self
+    }
+
+    pub fn with_fg(mut self, color: Color) -> Self {
+        self.fg = Some(color);
+        self
+    }
+}
+
+impl From<&str> for TermBigText {
+    fn from(s: &str) -> Self {
+        Self {
+            text: s.to_string(),
+            font: TermFont::standard(),
+            fg: None,
+        }
+    }
+}
+
+#[derive(Bundle)]
+pub struct TermBigTextBundle {
+    pub text: TermBigText,
+    pub align: TermTextAlign,
+    pub transform: Transform,
+}
+
+impl Default for TermBigTextBundle {
+    fn default() -> Self {
+        Self {
+            text: TermBigText::from("?"),
+            align: TermTextAlign::CENTER,
+            transform: Transform::from_xyz(0.0, 0.0, 0.0),
+        }
+    }
+}
+
+/// An RGBA image drawn with the kitty graphics protocol. Terminals that do not support it get the
+/// `fallback` char drawn instead.
+///
+/// The terminal draws images over the text, so the depth of the entity only decides if it is drawn
+/// at all: cells with a higher z do not cover an image, and `minz` hides it like anything else.
 #[derive(Component)]
+pub struct TermImage {
+    pub width: u32,
+    pub height: u32,
+    /// Pixels as 8 bit RGBA, row by row
+    pub data: Vec<u8>,
+    /// Size in cells, the natural pixel size is used if this is not set
+    pub cells: Option<(u32, u32)>,
+    pub fallback: char,
+}
+
+impl TermImage {
+    pub fn rgba(width: u32, height: u32, data: Vec<u8>) -> Self {
+        Self {
+            width,
+            height,
+            data,
+            cells: None,
+            fallback: '?',
+        }
+    }
+}
+
+#[derive(Bundle)]
+pub struct TermImageBundle {
+    pub image: TermImage,
+    pub transform: Transform,
+}
+
+impl Default for TermImageBundle {
+    fn default() -> Self {
+        Self {
+            image: TermImage::rgba(1, 1, vec![255, 255, 255, 255]),
+            transform: Transform::from_xyz(0.0, 0.0, 0.0),
+        }
+    }
+}
+
+/// What the terminal we are running in can do. Detected from the environment on startup, insert
+/// your own before adding the [`TermPlugin`] to override it.
+#[derive(Resource)]
+pub struct TermCapabilities {
+    pub kitty_graphics: bool,
+    /// OSC 8 hyperlinks, most terminals that do not support them ignore them
+    pub hyperlinks: bool,
+    /// OSC 52 clipboard writes, used to copy from a [`TermTextArea`] to the system clipboard
+    pub clipboard: bool,
+    /// Glyphs outside of ASCII, without this they are drawn with the [`TermGlyphFallback`]. Set
+    /// `BEVY_TERMINAL_ASCII=1` to turn it off.
+    pub unicode: bool,
+}
+
+/// ASCII stand-ins for glyphs, used for the whole frame when the terminal has no
+/// [`TermCapabilities::unicode`]. Box drawing, arrows and blocks have stand-ins by default, wide
+/// glyphs such as emoji without one are drawn as `emoji`, and anything else is drawn as is.
+#[derive(Resource)]
+pub struct TermGlyphFallback {
+    pub glyphs: HashMap<char, char>,
+    pub emoji: char,
+}
+
+#[derive(Component, Default)]
+#[require(TermCameraAspect)]
 pub struct TermCamera;
 
+/// Width of a terminal cell divided by its height, for the [`TermCamera`]. World units are scaled
+/// by this vertically so shapes keep their proportions, cells are usually about twice as tall as
+/// they are wide.
+#[derive(Component)]
+pub struct TermCameraAspect {
+    pub aspect: f32,
+}
+
+impl Default for TermCameraAspect {
+    fn default() -> Self {
+        Self { aspect: 0.5 }
+    }
+}
+
+/// Zoom for the [`TermCamera`], in world units per cell. Values above 1 zoom out and values below 1
+/// zoom in. This is multiplied with the scale of the camera transform.
+#[derive(Component)]
+pub struct TermProjection {
+    pub scale: f32,
+}
+
+impl Default for TermProjection {
+    fn default() -> Self {
+        Self { scale: 1.0 }
+    }
+}
+
+/// Perspective projection for the [`TermCamera`]. The camera looks down its local -z axis, like
+/// bevy cameras do, and things closer than `near` or further away than `far` are not drawn.
+#[derive(Component)]
+pub struct TermPerspective {
+    /// Vertical field of view in radians
+    pub fov: f32,
+    pub near: f32,
+    pub far: f32,
+}
+
+impl Default for TermPerspective {
+    fn default() -> Self {
+        Self {
+            fov: std::f32::consts::FRAC_PI_4,
+            near: 0.1,
+            far: 1000.0,
+        }
+    }
+}
+
 #[derive(Bundle)]
 pub struct TermCameraBundle {
     pub camera: TermCamera,
@@ -1,11 +1,31 @@
+use bevy::math::Affine3A;
 use bevy::prelude::*;
 
-use crate::prelude::{TermCamera, TermChar, TermText, TermTextAlign};
+use crate::draw::{shape_outline, Canvas};
+use crate::figlet;
+use crate::input::{cursor_column, scroll_to_cursor, selection_range};
+use crate::prelude::{
+    TermAnchor, TermArt, TermBigText, TermBorder, TermCamera, TermCameraAspect, TermCapabilities,
+    TermCell, TermChar, TermCursorStyle, TermGlyphFallback, TermImage, TermLine, TermLink,
+    TermParticleEmitter, TermParticles, TermPerspective, TermProjection, TermShape, TermShapeStyle,
+    TermText, TermTextAlign, TermTextArea, TermTextBounds, TermTextEffect, TermTextInput,
+    TermTilemap,
+};
 use crate::term::TermBuffer;
+use crate::text::{apply_effect, layout, reorder};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 #[derive(Resource)]
-struct RenderContext {
-    minz: f32,
+pub(crate) struct RenderContext {
+    pub minz: f32,
+}
+
+impl RenderContext {
+    /// Only things above `minz` in world space are drawn, whatever the camera does
+    pub(crate) fn visible(&self, transform: &GlobalTransform) -> bool {
+        transform.translation().z > self.minz
+    }
 }
 
 pub(crate) struct RenderPlugin {
@@ -15,7 +35,281 @@ pub(crate) struct RenderPlugin {
 impl Plugin for RenderPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(RenderContext { minz: self.minz })
-            .add_systems(PostUpdate, render);
+            .init_resource::<TermGlyphFallback>()
+            .add_systems(
+                PostUpdate,
+                (
+                    render,
+                    render_big_text,
+                    render_text_inputs,
+                    render_text_areas,
+                    render_tilemaps,
+                    render_art,
+                    render_particles,
+                    render_lines,
+                    render_shapes,
+                    render_borders,
+                    render_prune,
+                    render_fallback.run_if(no_unicode),
+                )
+                    .chain(),
+            );
+
+        #[cfg(feature = "sprite")]
+        app.add_systems(
+            PostUpdate,
+            render_sprites.after(render).before(render_prune),
+        );
+    }
+}
+
+type TextQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static GlobalTransform,
+        &'static TermText,
+        &'static TermTextAlign,
+        Option<&'static TermTextBounds>,
+        Option<&'static TermAnchor>,
+        Option<&'static TermTextEffect>,
+        Option<&'static TermLink>,
+    ),
+>;
+
+type BigTextQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static GlobalTransform,
+        &'static TermBigText,
+        &'static TermTextAlign,
+        Option<&'static TermAnchor>,
+        Option<&'static TermLink>,
+    ),
+>;
+
+pub(crate) type CameraQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static GlobalTransform,
+        &'static TermCameraAspect,
+        Option<&'static TermProjection>,
+        Option<&'static TermPerspective>,
+    ),
+    With<TermCamera>,
+>;
+
+enum Projection {
+    Orthographic,
+    Perspective { near: f32, far: f32 },
+}
+
+/// Maps world positions to terminal cells as seen through the [`TermCamera`]
+pub(crate) struct TermView {
+    world_to_view: Affine3A,
+    view_to_world: Affine3A,
+    projection: Projection,
+    center: (isize, isize),
+    scale: Vec2,
+    aspect: f32,
+    c: usize,
+    r: usize,
+}
+
+impl TermView {
+    pub(crate) fn new(camera: &CameraQuery, c: usize, r: usize) -> Self {
+        let (view_to_world, projection, center, scale, aspect) = match camera.single() {
+            Err(_) => (
+                Affine3A::IDENTITY,
+                Projection::Orthographic,
+                (0, 0),
+                Vec2::ONE,
+                1.0,
+            ),
+            Ok((transform, camera, zoom, perspective)) => {
+                let zoom = zoom.map_or(1.0, |p| p.scale);
+
+                let (projection, scale) = match perspective {
+                    None => (Projection::Orthographic, Vec2::new(1.0, camera.aspect)),
+                    Some(perspective) => {
+                        // Half the terminal height covers half of the field of view
+                        let half = r as f32 / 2.0;
+                        let focal = 1.0 / (perspective.fov / 2.0).tan();
+                        (
+                            Projection::Perspective {
+                                near: perspective.near,
+                                far: perspective.far,
+                            },
+                            Vec2::new(half / camera.aspect, half) * focal,
+                        )
+                    }
+                };
+
+                (
+                    transform.affine(),
+                    projection,
+                    ((c / 2) as isize, (r / 2) as isize),
+                    scale / zoom,
+                    camera.aspect,
+                )
+            }
+        };
+
+        // The inverse camera transform takes care of translation, rotation and scale
+        Self {
+            world_to_view: view_to_world.inverse(),
+            view_to_world,
+            projection,
+            center,
+            scale,
+            aspect,
+            c,
+            r,
+        }
+    }
+
+    /// Direction the camera is looking in world space
+    #[cfg(feature = "mesh")]
+    pub(crate) fn forward(&self) -> Vec3 {
+        self.view_to_world
+            .transform_vector3(Vec3::NEG_Z)
+            .normalize_or_zero()
+    }
+
+    /// If a surface at a world position with the given normal is facing the camera
+    #[cfg(feature = "mesh")]
+    pub(crate) fn faces_camera(&self, point: Vec3, normal: Vec3) -> bool {
+        let point = self.world_to_view.transform_point3(point);
+        let normal = self.world_to_view.transform_vector3(normal);
+
+        match self.projection {
+            Projection::Orthographic => normal.z > 0.0,
+            Projection::Perspective { .. } => normal.dot(-point) > 0.0,
+        }
+    }
+
+    /// Ray in world space going through a position in cells, away from the camera
+    pub(crate) fn ray(&self, x: f32, y: f32) -> (Vec3, Vec3) {
+        let x = (x - self.center.0 as f32) / self.scale.x;
+        let y = -(y - self.center.1 as f32) / self.scale.y;
+
+        match self.projection {
+            Projection::Orthographic => (
+                self.view_to_world.transform_point3(Vec3::new(x, y, 0.0)),
+                self.view_to_world.transform_vector3(Vec3::NEG_Z),
+            ),
+            Projection::Perspective { .. } => (
+                self.view_to_world.transform_point3(Vec3::ZERO),
+                self.view_to_world.transform_vector3(Vec3::new(x, y, -1.0)),
+            ),
+        }
+    }
+
+    /// Width of a cell divided by its height
+    pub(crate) fn aspect(&self) -> f32 {
+        self.aspect
+    }
+
+    /// Column, row and depth of a world position, may be outside of the terminal. Nothing is
+    /// returned if the position is clipped by the projection. Higher depth is closer to the camera.
+    pub(crate) fn project(&self, translation: Vec3) -> Option<(isize, isize, f32)> {
+        let cell = self.project_exact(translation)?;
+
+        Some((cell.x.floor() as isize, cell.y.floor() as isize, cell.z))
+    }
+
+    /// Same as [`TermView::project`], without rounding down to whole cells
+    pub(crate) fn project_exact(&self, translation: Vec3) -> Option<Vec3> {
+        let view = self.world_to_view.transform_point3(translation);
+        self.clip_planes()
+            .iter()
+            .all(|(normal, offset)| normal.dot(view) + offset >= 0.0)
+            .then(|| self.view_to_cell(view))
+    }
+
+    /// Cells of the segment between two world positions. It is cut where it crosses the near or far
+    /// plane, and nothing is returned if all of it is clipped.
+    pub(crate) fn project_segment(&self, from: Vec3, to: Vec3) -> Option<(Vec3, Vec3)> {
+        let mut from = self.world_to_view.transform_point3(from);
+        let mut to = self.world_to_view.transform_point3(to);
+
+        for (normal, offset) in self.clip_planes() {
+            let (a, b) = (normal.dot(from) + offset, normal.dot(to) + offset);
+            match (a >= 0.0, b >= 0.0) {
+                (true, true) => {}
+                (false, false) => return None,
+                (true, false) => to = from + (to - from) * (a / (a - b)),
+                (false, true) => from += (to - from) * (a / (a - b)),
+            }
+        }
+
+        Some((self.view_to_cell(from), self.view_to_cell(to)))
+    }
+
+    /// Cells of the corners of a polygon given in world positions, cut to the part between the
+    /// near and far planes. Nothing is returned if all of it is clipped.
+    pub(crate) fn project_polygon(&self, points: impl IntoIterator<Item = Vec3>) -> Vec<Vec3> {
+        let mut polygon: Vec<Vec3> = points
+            .into_iter()
+            .map(|p| self.world_to_view.transform_point3(p))
+            .collect();
+
+        // Sutherland-Hodgman, one plane at a time
+        for (normal, offset) in self.clip_planes() {
+            let mut clipped = Vec::with_capacity(polygon.len() + 1);
+            for (i, from) in polygon.iter().enumerate() {
+                let to = polygon[(i + 1) % polygon.len()];
+                let (a, b) = (normal.dot(*from) + offset, normal.dot(to) + offset);
+                if a >= 0.0 {
+                    clipped.push(*from);
+                }
+                if (a >= 0.0) != (b >= 0.0) {
+                    clipped.push(*from + (to - *from) * (a / (a - b)));
+                }
+            }
+            polygon = clipped;
+        }
+
+        polygon.into_iter().map(|p| self.view_to_cell(p)).collect()
+    }
+
+    /// Planes in view space that things are clipped by, as a normal pointing inside and an offset.
+    /// An orthographic projection does not clip, so everything is inside of its planes.
+    fn clip_planes(&self) -> [(Vec3, f32); 2] {
+        match self.projection {
+            Projection::Orthographic => [(Vec3::ZERO, 0.0); 2],
+            Projection::Perspective { near, far } => [(Vec3::NEG_Z, -near), (Vec3::Z, far)],
+        }
+    }
+
+    /// Cell of a view space position that is not clipped
+    fn view_to_cell(&self, view: Vec3) -> Vec3 {
+        let (x, y) = match self.projection {
+            Projection::Orthographic => (view.x, view.y),
+            Projection::Perspective { .. } => (view.x / -view.z, view.y / -view.z),
+        };
+
+        Vec3::new(
+            x * self.scale.x + self.center.0 as f32,
+            -y * self.scale.y + self.center.1 as f32,
+            view.z,
+        )
+    }
+
+    /// Column, row and depth of a world position, if it is inside of the terminal
+    pub(crate) fn cell(&self, translation: Vec3) -> Option<(usize, usize, f32)> {
+        let (x, y, z) = self.project(translation)?;
+
+        if x < 0 || y < 0 {
+            return None;
+        }
+
+        let x = x as usize;
+        let y = y as usize;
+
+        (x < self.c && y < self.r).then_some((x, y, z))
     }
 }
 
@@ -33,9 +327,11 @@ impl Plugin for RenderPlugin {
 fn render(
     context: Res<RenderContext>,
     terminal_buffer: ResMut<TermBuffer>,
-    camera: Query<&GlobalTransform, With<TermCamera>>,
+    camera: CameraQuery,
     chars: Query<(&GlobalTransform, &TermChar)>,
-    texts: Query<(&GlobalTransform, &TermText, &TermTextAlign)>,
+    texts: TextQuery,
+    images: Query<(&GlobalTransform, &TermImage)>,
+    capabilities: Res<TermCapabilities>,
 ) {
     // Prepare drawing
     let ctx = context.into_inner();
@@ -43,79 +339,693 @@ fn render(
     let (c, r) = terminal_buffer.size();
 
     // Calculate camera offset
-    let (camera_offset_x, camera_offset_y) = match camera.single() {
-        Err(_) => (0, 0),
-        Ok(camera) => {
-            let camera_x = camera.translation().x.round() as isize;
-            let camera_y = camera.translation().y.round() as isize;
+    let view = TermView::new(&camera, c, r);
 
-            let camera_offset_x = (-camera_x + (c as isize)) / 2;
-            let camera_offset_y = (camera_y + (r as isize)) / 2;
+    // Fill buffer with chars
+    for (transform, char) in chars
+        .iter()
+        .filter(|(transform, ..)| ctx.visible(transform))
+    {
+        let Some((x, y, z)) = view.cell(transform.translation()) else {
+            // This char is not in view
+            continue;
+        };
+
+        terminal_buffer.write(x, y, char.0.into(), z);
+    }
 
-            (camera_offset_x, camera_offset_y)
+    // Fill buffer with image fallbacks if the terminal cannot draw them
+    if !capabilities.kitty_graphics {
+        for (transform, image) in images
+            .iter()
+            .filter(|(transform, ..)| ctx.visible(transform))
+        {
+            let Some((x, y, z)) = view.cell(transform.translation()) else {
+                // This image is not in view
+                continue;
+            };
+
+            terminal_buffer.write(x, y, image.fallback.into(), z);
         }
-    };
+    }
 
-    // Fill buffer with chars
-    for (transform, char) in chars.iter() {
-        let x = transform.translation().x.floor() as isize;
-        let y = transform.translation().y.floor() as isize;
-        let z = transform.translation().z;
+    // Fill buffer with text
+    let mut canvas = Canvas::new(terminal_buffer);
+    for (transform, text, align, bounds, anchor, effect, link) in texts
+        .iter()
+        .filter(|(transform, ..)| ctx.visible(transform))
+    {
+        let Some((x, y, z)) = view.project(transform.translation()) else {
+            // This string is not in view
+            continue;
+        };
+
+        let mut lines = layout(&text.0, bounds);
+        let Some(cell) = apply_effect(&mut lines, effect) else {
+            // This string is blinked out
+            continue;
+        };
+        reorder(&mut lines);
 
-        let x = x + camera_offset_x;
-        let y = -y + camera_offset_y;
+        let anchor = text_anchor(anchor, align);
+        canvas.set_link(link.map(|link| link.0.as_str()));
+        draw_lines(&mut canvas, &lines, (x, y, z.floor()), anchor, cell, false);
+    }
+}
 
-        if x < 0 || y < 0 {
-            // This char is not in view
+fn render_big_text(
+    context: Res<RenderContext>,
+    terminal_buffer: ResMut<TermBuffer>,
+    camera: CameraQuery,
+    texts: BigTextQuery,
+) {
+    let terminal_buffer = terminal_buffer.into_inner();
+    let (c, r) = terminal_buffer.size();
+    let view = TermView::new(&camera, c, r);
+    let mut canvas = Canvas::new(terminal_buffer);
+
+    for (transform, text, align, anchor, link) in texts
+        .iter()
+        .filter(|(transform, ..)| context.visible(transform))
+    {
+        let Some((x, y, z)) = view.project(transform.translation()) else {
+            // This text is not in view
+            continue;
+        };
+
+        let lines = figlet::render(&text.font, &text.text);
+        let cell = TermCell {
+            glyph: ' ',
+            fg: text.fg,
+            bg: None,
+        };
+        // Letters have a lot of space around them, let whatever is behind show through it
+        let anchor = text_anchor(anchor, align);
+        canvas.set_link(link.map(|link| link.0.as_str()));
+        draw_lines(&mut canvas, &lines, (x, y, z.floor()), anchor, cell, true);
+    }
+}
+
+fn render_text_inputs(
+    context: Res<RenderContext>,
+    terminal_buffer: ResMut<TermBuffer>,
+    camera: CameraQuery,
+    inputs: Query<(&GlobalTransform, &TermTextInput)>,
+) {
+    let terminal_buffer = terminal_buffer.into_inner();
+    let (c, r) = terminal_buffer.size();
+    let view = TermView::new(&camera, c, r);
+    let mut canvas = Canvas::new(terminal_buffer);
+
+    for (transform, input) in inputs
+        .iter()
+        .filter(|(transform, ..)| context.visible(transform))
+    {
+        let Some((x, y, z)) = view.project(transform.translation()) else {
+            // This input is not in view
             continue;
+        };
+        let z = z.floor();
+
+        let (text, fg) = if input.value.is_empty() {
+            (input.placeholder.as_str(), input.placeholder_fg)
+        } else {
+            (input.value.as_str(), input.fg)
+        };
+        let cursor = input.cursor.min(input.value.graphemes(true).count());
+        let scroll = scroll_to_cursor(&input.value, cursor, input.width, input.scroll);
+
+        // Draw the visible part of the value, blanking the rest of the field
+        let cell = TermCell {
+            glyph: ' ',
+            fg,
+            bg: None,
+        };
+        let mut column = 0;
+        for grapheme in text.graphemes(true) {
+            let width = grapheme.width();
+            if column >= scroll && column + width <= scroll + input.width {
+                canvas.put_cluster(x + (column - scroll) as isize, y, grapheme, cell, z);
+            }
+            column += width;
         }
 
-        let x = x as usize;
-        let y = y as usize;
+        let start = column.saturating_sub(scroll).min(input.width);
+        for column in start..input.width {
+            canvas.put(x + column as isize, y, ' '.into(), z);
+        }
+
+        if !input.focused {
+            continue;
+        }
 
-        if x < c && y < r && z > ctx.minz {
-            terminal_buffer.write(x, y, char.0, z);
+        let at = x + (cursor_column(&input.value, cursor) - scroll) as isize;
+        match input.cursor_style {
+            TermCursorStyle::Hardware => canvas.cursor(at, y),
+            TermCursorStyle::Cell { fg, bg } => {
+                // Drawn just in front of the field, the cursor is a blank cell at the end
+                let grapheme = text.graphemes(true).nth(cursor).unwrap_or(" ");
+                let cell = TermCell { glyph: ' ', fg, bg };
+                canvas.put_cluster(at, y, grapheme, cell, z + 0.5);
+            }
         }
     }
+}
 
-    // Fill buffer with text
-    for (transform, text, align) in texts.iter() {
-        let text = &text.0;
-        let text_len = text.len();
-        let x = transform.translation().x.floor() as isize;
-        let y = transform.translation().y.floor() as isize;
-        let z = transform.translation().z.floor();
+fn render_text_areas(
+    context: Res<RenderContext>,
+    terminal_buffer: ResMut<TermBuffer>,
+    camera: CameraQuery,
+    areas: Query<(&GlobalTransform, &TermTextArea)>,
+) {
+    let terminal_buffer = terminal_buffer.into_inner();
+    let (c, r) = terminal_buffer.size();
+    let view = TermView::new(&camera, c, r);
+    let mut canvas = Canvas::new(terminal_buffer);
 
-        let x = x + camera_offset_x;
-        let y = -y + camera_offset_y;
+    for (transform, area) in areas
+        .iter()
+        .filter(|(transform, ..)| context.visible(transform))
+    {
+        let Some((x, y, z)) = view.project(transform.translation()) else {
+            // This area is not in view
+            continue;
+        };
+        let z = z.floor();
+
+        let selection = selection_range(area);
+        let (scroll_line, scroll_column) = area.scroll;
+        for row in 0..area.height {
+            let line = scroll_line + row;
+            let y = y + row as isize;
+            let text = area.lines.get(line).map_or("", String::as_str);
 
-        let x = match align {
-            TermTextAlign::LEFT => x,
-            TermTextAlign::CENTER => x - (text_len as isize / 2),
-            TermTextAlign::RIGHT => x - text_len as isize,
+            // Draw the visible part of the line, blanking the rest of the row
+            let mut column = 0;
+            for (i, grapheme) in text.graphemes(true).enumerate() {
+                let width = grapheme.width();
+                if column >= scroll_column && column + width <= scroll_column + area.width {
+                    let selected = selection.is_some_and(|(s, e)| s <= (line, i) && (line, i) < e);
+                    let cell = if selected {
+                        TermCell {
+                            glyph: ' ',
+                            fg: area.selection_fg,
+                            bg: area.selection_bg,
+                        }
+                    } else {
+                        TermCell {
+                            glyph: ' ',
+                            fg: area.fg,
+                            bg: None,
+                        }
+                    };
+                    let at = x + (column - scroll_column) as isize;
+                    canvas.put_cluster(at, y, grapheme, cell, z);
+                }
+                column += width;
+            }
+
+            let start = column.saturating_sub(scroll_column).min(area.width);
+            for column in start..area.width {
+                canvas.put(x + column as isize, y, ' '.into(), z);
+            }
+        }
+
+        let (line, column) = area.cursor;
+        let Some(text) = area.lines.get(line) else {
+            continue;
         };
+        if !area.focused || line < scroll_line || line >= scroll_line + area.height {
+            continue;
+        }
 
-        if x + (text_len as isize) < 0 || y < 0 {
-            // This string is not in view
+        let cursor_column = cursor_column(text, column);
+        if cursor_column < scroll_column || cursor_column >= scroll_column + area.width {
             continue;
         }
 
-        let x = x as usize;
-        let y = y as usize;
+        let at = x + (cursor_column - scroll_column) as isize;
+        let y = y + (line - scroll_line) as isize;
+        match area.cursor_style {
+            TermCursorStyle::Hardware => canvas.cursor(at, y),
+            TermCursorStyle::Cell { fg, bg } => {
+                let grapheme = text.graphemes(true).nth(column).unwrap_or(" ");
+                let cell = TermCell { glyph: ' ', fg, bg };
+                canvas.put_cluster(at, y, grapheme, cell, z + 0.5);
+            }
+        }
+    }
+}
 
-        if x > c || y > r {
-            // This string is not in view
+fn text_anchor(anchor: Option<&TermAnchor>, align: &TermTextAlign) -> Vec2 {
+    match (anchor, align) {
+        (Some(anchor), _) => Vec2::from(*anchor),
+        (None, TermTextAlign::LEFT) => Vec2::new(0.0, 0.0),
+        (None, TermTextAlign::CENTER) => Vec2::new(0.5, 0.0),
+        (None, TermTextAlign::RIGHT) => Vec2::new(1.0, 0.0),
+    }
+}
+
+/// How far a block of `size` cells starts before the entity, for a part of an anchor. Text and
+/// art share this so they line up with each other.
+fn anchor_offset(size: usize, anchor: f32) -> isize {
+    (size as f32 * anchor).floor() as isize
+}
+
+/// Draw lines of text one grapheme cluster at a time, so emoji and combining marks stay whole
+fn draw_lines(
+    canvas: &mut Canvas,
+    lines: &[String],
+    (x, y, z): (isize, isize, f32),
+    anchor: Vec2,
+    cell: TermCell,
+    transparent: bool,
+) {
+    let (c, r) = canvas.size();
+    let y = y - anchor_offset(lines.len(), anchor.y);
+
+    for (row, line) in lines.iter().enumerate() {
+        let y = y + row as isize;
+        let width = line.width() as isize;
+        let mut x = x - anchor_offset(width as usize, anchor.x);
+
+        if x + width < 0 || y < 0 || x >= c as isize || y >= r as isize {
+            // This line is not in view
+            continue;
+        }
+
+        for cluster in line.graphemes(true) {
+            if transparent && cluster == " " {
+                x += 1;
+                continue;
+            }
+            x += canvas.put_cluster(x, y, cluster, cell, z) as isize;
+        }
+    }
+}
+
+/// Draws bevy sprites as cells colored by their image and tint. Texture atlases are not supported,
+/// use `Sprite::rect` to pick a part of the image instead.
+#[cfg(feature = "sprite")]
+fn render_sprites(
+    context: Res<RenderContext>,
+    terminal_buffer: ResMut<TermBuffer>,
+    camera: CameraQuery,
+    images: Option<Res<Assets<Image>>>,
+    sprites: Query<(&GlobalTransform, &Sprite)>,
+) {
+    let terminal_buffer = terminal_buffer.into_inner();
+    let (c, r) = terminal_buffer.size();
+    let view = TermView::new(&camera, c, r);
+    let mut canvas = Canvas::new(terminal_buffer);
+
+    for (transform, sprite) in sprites
+        .iter()
+        .filter(|(transform, ..)| context.visible(transform))
+    {
+        let image = images.as_ref().and_then(|images| images.get(&sprite.image));
+        let region = sprite
+            .rect
+            .or_else(|| image.map(|image| Rect::from_corners(Vec2::ZERO, image.size().as_vec2())));
+
+        let Some(size) = sprite.custom_size.or(region.map(|region| region.size())) else {
+            // The image is not loaded yet, so we do not know how big the sprite is
+            continue;
+        };
+
+        // Position of a point on the sprite, from 0 to 1 going right and up
+        let anchor = sprite.anchor.as_vec();
+        let local = |u: f32, v: f32| ((Vec2::new(u, v) - 0.5 - anchor) * size).extend(0.0);
+        let project = |u: f32, v: f32| view.project_exact(transform.transform_point(local(u, v)));
+
+        let (Some(origin), Some(right), Some(up)) =
+            (project(0.0, 0.0), project(1.0, 0.0), project(0.0, 1.0))
+        else {
+            // This sprite is clipped by the projection
             continue;
+        };
+
+        // Sample twice per cell the sprite covers, so no cells are skipped
+        let steps = |to: Vec3| {
+            ((to - origin).truncate().length() * 2.0)
+                .ceil()
+                .clamp(1.0, 1024.0) as usize
+        };
+        let (steps_u, steps_v) = (steps(right), steps(up));
+        let tint = sprite.color.to_linear();
+
+        for j in 0..steps_v {
+            for i in 0..steps_u {
+                let u = (i as f32 + 0.5) / steps_u as f32;
+                let v = (j as f32 + 0.5) / steps_v as f32;
+
+                let color = match (image, region) {
+                    (Some(image), Some(region)) => {
+                        let u = if sprite.flip_x { 1.0 - u } else { u };
+                        let v = if sprite.flip_y { 1.0 - v } else { v };
+
+                        // Image rows go downwards
+                        let x = (region.min.x + u * region.width()).min(region.max.x - 1.0);
+                        let y =
+                            (region.min.y + (1.0 - v) * region.height()).min(region.max.y - 1.0);
+                        image
+                            .get_color_at(x.max(0.0) as u32, y.max(0.0) as u32)
+                            .ok()
+                    }
+                    _ => Some(Color::WHITE),
+                };
+
+                let Some(color) = color.map(|color| color.to_linear()) else {
+                    continue;
+                };
+                let color = LinearRgba::new(
+                    color.red * tint.red,
+                    color.green * tint.green,
+                    color.blue * tint.blue,
+                    color.alpha * tint.alpha,
+                );
+                if color.alpha < 0.5 {
+                    // Transparent pixels leave the cell as it is
+                    continue;
+                }
+
+                let Some(cell) = project(u, v) else {
+                    continue;
+                };
+                canvas.put(
+                    cell.x.floor() as isize,
+                    cell.y.floor() as isize,
+                    TermCell {
+                        glyph: ' ',
+                        fg: None,
+                        bg: Some(color.into()),
+                    },
+                    cell.z,
+                );
+            }
+        }
+    }
+}
+
+impl TermTilemap {
+    pub fn new(width: usize, height: usize, tile: TermCell) -> Self {
+        Self {
+            width,
+            height,
+            tiles: vec![tile; width * height],
+            tile_size: Vec2::new(1.0, 2.0),
+        }
+    }
+
+    fn index(&self, x: usize, y: usize) -> Option<usize> {
+        (x < self.width && y < self.height).then_some(y * self.width + x)
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&TermCell> {
+        self.index(x, y).and_then(|i| self.tiles.get(i))
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut TermCell> {
+        self.index(x, y).and_then(|i| self.tiles.get_mut(i))
+    }
+
+    /// Set a tile, tiles outside of the map are ignored
+    pub fn set(&mut self, x: usize, y: usize, tile: TermCell) {
+        if let Some(t) = self.get_mut(x, y) {
+            *t = tile;
+        }
+    }
+
+    /// Set every tile in a region, the parts outside of the map are ignored
+    pub fn fill(
+        &mut self,
+        (x, y): (usize, usize),
+        (width, height): (usize, usize),
+        tile: TermCell,
+    ) {
+        for ty in y..(y + height).min(self.height) {
+            for tx in x..(x + width).min(self.width) {
+                self.set(tx, ty, tile);
+            }
         }
+    }
+
+    /// Copy a region of tiles to another place in the map. The regions may overlap, and the parts
+    /// outside of the map are ignored.
+    pub fn copy_region(
+        &mut self,
+        (x, y): (usize, usize),
+        (width, height): (usize, usize),
+        (to_x, to_y): (usize, usize),
+    ) {
+        let mut region = Vec::with_capacity(width * height);
+        for ty in y..(y + height).min(self.height) {
+            for tx in x..(x + width).min(self.width) {
+                region.push((tx - x, ty - y, self.tiles[ty * self.width + tx]));
+            }
+        }
+
+        for (dx, dy, tile) in region {
+            self.set(to_x + dx, to_y + dy, tile);
+        }
+    }
+}
+
+fn render_tilemaps(
+    context: Res<RenderContext>,
+    terminal_buffer: ResMut<TermBuffer>,
+    camera: CameraQuery,
+    tilemaps: Query<(&GlobalTransform, &TermTilemap)>,
+) {
+    let terminal_buffer = terminal_buffer.into_inner();
+    let (c, r) = terminal_buffer.size();
+    let view = TermView::new(&camera, c, r);
+    let mut canvas = Canvas::new(terminal_buffer);
+
+    for (transform, tilemap) in tilemaps
+        .iter()
+        .filter(|(transform, ..)| context.visible(transform))
+    {
+        let world_to_local = transform.affine().inverse();
+
+        // Look up the tile under every cell, so only visible tiles are ever touched
+        for y in 0..r {
+            for x in 0..c {
+                let (origin, direction) = view.ray(x as f32 + 0.5, y as f32 + 0.5);
+                let origin = world_to_local.transform_point3(origin);
+                let direction = world_to_local.transform_vector3(direction);
+
+                // Where the ray hits the plane of the tilemap
+                if direction.z == 0.0 {
+                    continue;
+                }
+                let distance = -origin.z / direction.z;
+                let hit = origin + direction * distance;
+
+                // Tiles go right and down from the entity
+                let tile_x = (hit.x / tilemap.tile_size.x).floor();
+                let tile_y = (-hit.y / tilemap.tile_size.y).floor();
+                if tile_x < 0.0 || tile_y < 0.0 {
+                    continue;
+                }
 
-        for (i, char) in text.chars().enumerate() {
-            let x = x + i;
+                let Some(tile) = tilemap.get(tile_x as usize, tile_y as usize) else {
+                    continue;
+                };
+                if tile.glyph == ' ' && tile.bg.is_none() {
+                    // Empty tiles are see through
+                    continue;
+                }
 
-            if x < c && y < r && z > ctx.minz {
-                terminal_buffer.write(x, y, char, z);
+                let Some(cell) = view.project_exact(transform.transform_point(hit)) else {
+                    // This tile is clipped by the projection
+                    continue;
+                };
+                canvas.put(x as isize, y as isize, *tile, cell.z);
             }
         }
     }
+}
+
+fn render_art(
+    context: Res<RenderContext>,
+    terminal_buffer: ResMut<TermBuffer>,
+    camera: CameraQuery,
+    arts: Query<(&GlobalTransform, &TermArt, Option<&TermAnchor>)>,
+) {
+    let terminal_buffer = terminal_buffer.into_inner();
+    let (c, r) = terminal_buffer.size();
+    let view = TermView::new(&camera, c, r);
+    let mut canvas = Canvas::new(terminal_buffer);
+
+    for (transform, art, anchor) in arts
+        .iter()
+        .filter(|(transform, ..)| context.visible(transform))
+    {
+        let Some((x, y, z)) = view.project(transform.translation()) else {
+            // This art is not in view
+            continue;
+        };
+
+        let width = art.art.lines().map(|l| l.width()).max().unwrap_or(0);
+        let height = art.art.lines().count();
+        let anchor = anchor.map_or(art.anchor, |anchor| Vec2::from(*anchor));
+        let x = x - anchor_offset(width, anchor.x);
+        let y = y - anchor_offset(height, anchor.y);
+
+        let transparent = art.transparent.map(String::from);
+        let mut mask = art.color_mask.as_deref().unwrap_or("").lines();
+        for (row, line) in art.art.lines().enumerate() {
+            let mut mask_line = mask.next().unwrap_or("").chars();
+            let mut col = 0;
+            for cluster in line.graphemes(true) {
+                // Wide glyphs take two columns, the mask still has one key per grapheme cluster
+                let key = mask_line.next();
+                let at = col;
+                col += cluster.width();
+                if transparent.as_deref() == Some(cluster) {
+                    continue;
+                }
+
+                let fg = key
+                    .and_then(|key| art.palette.iter().find(|(k, _)| *k == key))
+                    .map(|(_, color)| *color)
+                    .or(art.fg);
+                let cell = TermCell {
+                    glyph: cluster.chars().next().unwrap_or(' '),
+                    fg,
+                    bg: None,
+                };
+                canvas.put_cluster(x + at as isize, y + row as isize, cluster, cell, z);
+            }
+        }
+    }
+}
+
+fn render_particles(
+    context: Res<RenderContext>,
+    terminal_buffer: ResMut<TermBuffer>,
+    camera: CameraQuery,
+    emitters: Query<(&TermParticleEmitter, &TermParticles)>,
+) {
+    let terminal_buffer = terminal_buffer.into_inner();
+    let (c, r) = terminal_buffer.size();
+    let view = TermView::new(&camera, c, r);
+    let mut canvas = Canvas::new(terminal_buffer);
+
+    for (emitter, particles) in emitters.iter() {
+        if emitter.gradient.is_empty() {
+            continue;
+        }
+
+        for particle in particles
+            .particles
+            .iter()
+            .filter(|particle| particle.position.z > context.minz)
+        {
+            let Some((x, y, z)) = view.project(particle.position) else {
+                // This particle is not in view
+                continue;
+            };
+
+            let life = (particle.age / emitter.lifetime).clamp(0.0, 1.0);
+            let index =
+                ((life * emitter.gradient.len() as f32) as usize).min(emitter.gradient.len() - 1);
+            canvas.put(x, y, emitter.gradient[index], z);
+        }
+    }
+}
+
+fn render_lines(
+    context: Res<RenderContext>,
+    terminal_buffer: ResMut<TermBuffer>,
+    camera: CameraQuery,
+    lines: Query<(&GlobalTransform, &TermLine)>,
+) {
+    let terminal_buffer = terminal_buffer.into_inner();
+    let (c, r) = terminal_buffer.size();
+    let view = TermView::new(&camera, c, r);
+    let mut canvas = Canvas::new(terminal_buffer);
+
+    for (transform, line) in lines
+        .iter()
+        .filter(|(transform, ..)| context.visible(transform))
+    {
+        let from = transform.transform_point(line.from);
+        let to = transform.transform_point(line.to);
+        let Some((from, to)) = view.project_segment(from, to) else {
+            // This line is clipped by the projection
+            continue;
+        };
+
+        canvas.line(from, to, line.glyph.map(TermCell::from), view.aspect());
+    }
+}
+
+pub(crate) fn render_shapes(
+    context: Res<RenderContext>,
+    terminal_buffer: ResMut<TermBuffer>,
+    camera: CameraQuery,
+    shapes: Query<(&GlobalTransform, &TermShape, &TermShapeStyle)>,
+) {
+    let terminal_buffer = terminal_buffer.into_inner();
+    let (c, r) = terminal_buffer.size();
+    let view = TermView::new(&camera, c, r);
+    let mut canvas = Canvas::new(terminal_buffer);
+
+    for (transform, shape, style) in shapes
+        .iter()
+        .filter(|(transform, ..)| context.visible(transform))
+    {
+        let outline = shape_outline(shape).into_iter();
+        let points =
+            view.project_polygon(outline.map(|p| transform.transform_point(p.extend(0.0))));
+
+        canvas.polygon(&points, style.fill, style.stroke, view.aspect());
+    }
+}
+
+pub(crate) fn render_borders(
+    context: Res<RenderContext>,
+    terminal_buffer: ResMut<TermBuffer>,
+    camera: CameraQuery,
+    borders: Query<(&GlobalTransform, &TermBorder)>,
+) {
+    let terminal_buffer = terminal_buffer.into_inner();
+    let (c, r) = terminal_buffer.size();
+    let view = TermView::new(&camera, c, r);
+    let mut canvas = Canvas::new(terminal_buffer);
+
+    for (transform, border) in borders
+        .iter()
+        .filter(|(transform, ..)| context.visible(transform))
+    {
+        let half = border.size / 2.0;
+        let from = view.project(transform.transform_point(Vec3::new(-half.x, half.y, 0.0)));
+        let to = view.project(transform.transform_point(Vec3::new(half.x, -half.y, 0.0)));
+
+        let (Some((x0, y0, z)), Some((x1, y1, _))) = (from, to) else {
+            // This border is clipped by the projection
+            continue;
+        };
+
+        let cell = TermCell {
+            glyph: ' ',
+            fg: border.fg,
+            bg: None,
+        };
+        canvas.border((x0, y0), (x1, y1), &border.style, cell, z);
+    }
+}
+
+pub(crate) fn render_prune(terminal_buffer: ResMut<TermBuffer>) {
+    terminal_buffer.into_inner().prune()
+}
+
+fn no_unicode(capabilities: Res<TermCapabilities>) -> bool {
+    !capabilities.unicode
+}
 
-    terminal_buffer.prune()
+fn render_fallback(terminal_buffer: ResMut<TermBuffer>, fallback: Res<TermGlyphFallback>) {
+    terminal_buffer.into_inner().fallback(&fallback)
 }
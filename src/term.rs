@@ -1,18 +1,72 @@
-use super::prelude::{TermCommand, TermInput};
+use super::border::Edges;
+use super::prelude::{TermCapabilities, TermCell, TermCommand, TermGlyphFallback, TermInput};
+use bevy::platform::collections::HashMap;
 use bevy::prelude::*;
 use pancurses::{
-    curs_set, endwin, getmouse, initscr, mousemask, nl, noecho, resize_term, Input, Window,
-    ALL_MOUSE_EVENTS,
+    curs_set, endwin, getmouse, has_colors, init_pair, initscr, mousemask, nl, noecho, resize_term,
+    start_color, use_default_colors, Input, Window, ALL_MOUSE_EVENTS, COLORS, COLOR_PAIRS,
 };
+use std::io::Write;
+use std::sync::Arc;
 use tracing::warn;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
-const CLEAR: (char, f32) = (' ', f32::MIN);
+const CLEAR: BufferCell = BufferCell {
+    cell: TermCell {
+        glyph: ' ',
+        fg: None,
+        bg: None,
+    },
+    cluster: None,
+    width: 1,
+    z: f32::MIN,
+    edges: None,
+    link: None,
+};
+
+#[derive(Clone)]
+struct BufferCell {
+    cell: TermCell,
+    /// Set when the glyph is a grapheme cluster of more than one char
+    cluster: Option<Box<str>>,
+    /// Columns the glyph takes, the cell after a wide glyph is reserved with a width of 0
+    width: u8,
+    z: f32,
+    edges: Option<Edges>,
+    /// Hyperlink the cell is part of
+    link: Option<Arc<str>>,
+}
+
+impl BufferCell {
+    fn text(&self) -> String {
+        match &self.cluster {
+            Some(cluster) => cluster.to_string(),
+            None => self.cell.glyph.to_string(),
+        }
+    }
+}
+
+/// Cells on a row that share a hyperlink and colors, they are written out in one go
+#[derive(Clone, PartialEq)]
+struct LinkRun {
+    c: usize,
+    r: usize,
+    width: usize,
+    url: Arc<str>,
+    text: String,
+    fg: Option<Color>,
+    bg: Option<Color>,
+}
 
 #[derive(Resource)]
 pub(crate) struct TermBuffer {
-    buffer: Vec<Vec<(char, f32)>>,
+    buffer: Vec<Vec<BufferCell>>,
     c: usize,
     r: usize,
+    /// Where to show the terminal cursor this frame, it is hidden if this is not set
+    cursor: Option<(usize, usize)>,
+    /// Hyperlinks that are on the screen
+    links: Vec<LinkRun>,
 }
 
 impl TermBuffer {
@@ -21,6 +75,8 @@ impl TermBuffer {
             buffer: vec![vec![CLEAR; c as usize]; r as usize],
             c: c as usize,
             r: r as usize,
+            cursor: None,
+            links: Vec::new(),
         }
     }
 
@@ -28,21 +84,411 @@ impl TermBuffer {
         (self.c, self.r)
     }
 
-    pub(crate) fn write(&mut self, c: usize, r: usize, v: char, z: f32) {
-        if z > self.buffer[r][c].1 {
-            self.buffer[r][c] = (v, z)
+    pub(crate) fn set_cursor(&mut self, c: usize, r: usize) {
+        self.cursor = Some((c, r));
+    }
+
+    pub(crate) fn write(&mut self, c: usize, r: usize, v: TermCell, z: f32) {
+        let width = v.glyph.width().unwrap_or(0);
+        self.place(
+            c,
+            r,
+            BufferCell {
+                cell: v,
+                cluster: None,
+                width: width as u8,
+                z,
+                edges: None,
+                link: None,
+            },
+        );
+    }
+
+    /// Write a grapheme cluster, returns how many columns it takes
+    pub(crate) fn write_cluster(
+        &mut self,
+        c: usize,
+        r: usize,
+        cluster: &str,
+        v: TermCell,
+        z: f32,
+        link: Option<Arc<str>>,
+    ) -> usize {
+        let width = cluster.width();
+        let mut chars = cluster.chars();
+        let Some(glyph) = chars.next() else {
+            return 0;
+        };
+
+        self.place(
+            c,
+            r,
+            BufferCell {
+                cell: TermCell { glyph, ..v },
+                cluster: chars.next().is_some().then(|| cluster.into()),
+                width: width.min(u8::MAX as usize) as u8,
+                z,
+                edges: None,
+                link,
+            },
+        );
+        width
+    }
+
+    fn place(&mut self, c: usize, r: usize, v: BufferCell) {
+        // Zero width glyphs have nothing to attach to, and wide glyphs need room for both halves
+        let width = v.width as usize;
+        if width == 0 || width > 2 || c + width > self.c {
+            return;
+        }
+
+        if (c..c + width).any(|c| v.z <= self.buffer[r][c].z) {
+            return;
+        }
+
+        for c in c..c + width {
+            self.break_wide(c, r);
+        }
+
+        if width == 2 {
+            self.buffer[r][c + 1] = BufferCell {
+                cell: TermCell {
+                    glyph: ' ',
+                    ..v.cell
+                },
+                cluster: None,
+                width: 0,
+                z: v.z,
+                edges: None,
+                link: v.link.clone(),
+            };
+        }
+        self.buffer[r][c] = v;
+    }
+
+    /// Blank out the other half of a wide glyph that is partly being overwritten
+    fn break_wide(&mut self, c: usize, r: usize) {
+        let other = match self.buffer[r][c].width {
+            0 if c > 0 => c - 1,
+            2 if c + 1 < self.c => c + 1,
+            _ => return,
+        };
+
+        let cell = &mut self.buffer[r][other];
+        cell.cell.glyph = ' ';
+        cell.cluster = None;
+        cell.width = 1;
+    }
+
+    /// Write part of a border. Borders are joined with the borders already in the cell instead of
+    /// replacing them, regardless of depth.
+    pub(crate) fn write_border(&mut self, c: usize, r: usize, v: TermCell, z: f32, edges: Edges) {
+        match self.buffer[r][c].edges {
+            Some(cell_edges) => {
+                let cell = &mut self.buffer[r][c];
+                let edges = cell_edges.merge(edges);
+                cell.edges = Some(edges);
+                cell.cell.glyph = edges.glyph();
+                if z > cell.z {
+                    cell.cell.fg = v.fg;
+                    cell.cell.bg = v.bg;
+                    cell.z = z;
+                }
+            }
+            None if z > self.buffer[r][c].z => {
+                self.break_wide(c, r);
+                self.buffer[r][c] = BufferCell {
+                    cell: TermCell {
+                        glyph: edges.glyph(),
+                        ..v
+                    },
+                    cluster: None,
+                    width: 1,
+                    z,
+                    edges: Some(edges),
+                    link: None,
+                };
+            }
+            None => {}
         }
     }
 
+    /// Make sure every wide glyph still has its reserved cell, so nothing spills over the line
     pub(crate) fn prune(&mut self) {
-        // TODO: Prune buffer so it does not overflow if sum of char.len_utf8() is greater than
-        // buffer length
+        for row in self.buffer.iter_mut() {
+            for c in 0..row.len() {
+                let broken = match row[c].width {
+                    0 => c == 0 || row[c - 1].width != 2,
+                    2 => c + 1 >= row.len() || row[c + 1].width != 0,
+                    _ => false,
+                };
+
+                if broken {
+                    row[c].cell.glyph = ' ';
+                    row[c].cluster = None;
+                    row[c].width = 1;
+                }
+            }
+        }
+    }
+
+    /// Replace every glyph with its stand-in, wide glyphs that become narrow free their second cell
+    pub(crate) fn fallback(&mut self, fallback: &TermGlyphFallback) {
+        for row in self.buffer.iter_mut() {
+            for c in 0..row.len() {
+                let v = &mut row[c];
+                if v.width == 0 || (v.cell.glyph.is_ascii() && v.cluster.is_none()) {
+                    continue;
+                }
+
+                // Marks and joined emoji are dropped along with the rest of the cluster
+                let glyph = match v.cluster.take() {
+                    Some(_) if v.width == 2 => fallback.emoji,
+                    _ => fallback.glyph(v.cell.glyph),
+                };
+                v.cell.glyph = glyph;
+
+                let width = glyph.width().unwrap_or(1).clamp(1, 2) as u8;
+                if v.width == 2 && width == 1 {
+                    v.width = 1;
+                    if let Some(next) = row.get_mut(c + 1) {
+                        next.width = 1;
+                        next.cell.glyph = ' ';
+                    }
+                }
+            }
+        }
+    }
+
+    fn link_runs(&self) -> Vec<LinkRun> {
+        let mut runs = Vec::new();
+        for (r, row) in self.buffer.iter().enumerate() {
+            let mut run: Option<LinkRun> = None;
+            for (c, v) in row.iter().enumerate() {
+                if v.width == 0 {
+                    // The other half of a wide glyph that is already in the run
+                    if let Some(run) = run.as_mut() {
+                        run.width += 1;
+                    }
+                    continue;
+                }
+
+                let Some(url) = &v.link else {
+                    runs.extend(run.take());
+                    continue;
+                };
+
+                match run.as_mut() {
+                    Some(run) if run.url == *url && run.fg == v.cell.fg && run.bg == v.cell.bg => {
+                        run.width += 1;
+                        run.text.push_str(&v.text());
+                    }
+                    _ => {
+                        runs.extend(run.take());
+                        run = Some(LinkRun {
+                            c,
+                            r,
+                            width: 1,
+                            url: url.clone(),
+                            text: v.text(),
+                            fg: v.cell.fg,
+                            bg: v.cell.bg,
+                        });
+                    }
+                }
+            }
+            runs.extend(run);
+        }
+        runs
+    }
+}
+
+impl TermCapabilities {
+    pub fn detect() -> Self {
+        let env = |key: &str| std::env::var(key).unwrap_or_default();
+
+        let kitty_graphics = !env("KITTY_WINDOW_ID").is_empty()
+            || env("TERM").contains("kitty")
+            || env("TERM").contains("ghostty")
+            || matches!(env("TERM_PROGRAM").as_str(), "WezTerm" | "ghostty");
+
+        // The Linux console prints OSC 8 and OSC 52 sequences instead of ignoring them
+        let hyperlinks = !matches!(env("TERM").as_str(), "linux" | "dumb");
+        let clipboard = hyperlinks;
+
+        // The Linux console and serial terminals only have a small font, if any unicode at all
+        let locale = [env("LC_ALL"), env("LC_CTYPE"), env("LANG")]
+            .into_iter()
+            .find(|l| !l.is_empty())
+            .unwrap_or_default()
+            .to_uppercase();
+        let unicode = matches!(env("BEVY_TERMINAL_ASCII").as_str(), "" | "0")
+            && !matches!(env("TERM").as_str(), "linux" | "dumb" | "vt100" | "vt220")
+            && (locale.contains("UTF-8") || locale.contains("UTF8"));
+
+        Self {
+            kitty_graphics,
+            hyperlinks,
+            clipboard,
+            unicode,
+        }
+    }
+}
+
+impl Default for TermCapabilities {
+    fn default() -> Self {
+        Self::detect()
+    }
+}
+
+impl TermGlyphFallback {
+    pub fn glyph(&self, glyph: char) -> char {
+        if glyph.is_ascii() {
+            return glyph;
+        }
+
+        match self.glyphs.get(&glyph) {
+            Some(ascii) => *ascii,
+            None if glyph.width() == Some(2) => self.emoji,
+            None => glyph,
+        }
+    }
+}
+
+impl Default for TermGlyphFallback {
+    fn default() -> Self {
+        let mut glyphs: HashMap<char, char> = crate::border::ascii_glyphs().collect();
+        glyphs.extend([
+            ('←', '<'),
+            ('→', '>'),
+            ('↑', '^'),
+            ('↓', 'v'),
+            ('⇐', '<'),
+            ('⇒', '>'),
+            ('⇑', '^'),
+            ('⇓', 'v'),
+            ('…', '.'),
+            ('•', '*'),
+            ('·', '.'),
+            ('█', '#'),
+            ('▀', '#'),
+            ('▄', '#'),
+            ('▌', '#'),
+            ('▐', '#'),
+            ('░', '#'),
+            ('▒', '#'),
+            ('▓', '#'),
+        ]);
+
+        // Braille is used for fine mesh fills
+        glyphs.extend(('\u{2801}'..='\u{28ff}').map(|glyph| (glyph, '#')));
+        glyphs.insert('\u{2800}', ' ');
+
+        Self { glyphs, emoji: 'o' }
     }
 }
 
 #[derive(Resource)]
-struct Term {
+pub(crate) struct Term {
     window: Window,
+    colors: Option<TermColors>,
+    cursor_visible: bool,
+}
+
+/// Curses color pairs, allocated as different foreground and background combinations are drawn
+struct TermColors {
+    colors: i32,
+    pairs: HashMap<(i16, i16), i16>,
+    max_pairs: i16,
+}
+
+impl TermColors {
+    fn new() -> Option<Self> {
+        if !has_colors() {
+            return None;
+        }
+
+        start_color();
+        use_default_colors();
+
+        Some(Self {
+            colors: COLORS(),
+            pairs: HashMap::default(),
+            max_pairs: COLOR_PAIRS().min(i16::MAX as i32) as i16,
+        })
+    }
+
+    fn pair(&mut self, fg: Option<Color>, bg: Option<Color>) -> i16 {
+        if fg.is_none() && bg.is_none() {
+            return 0;
+        }
+
+        let key = (self.index(fg), self.index(bg));
+        if let Some(pair) = self.pairs.get(&key) {
+            return *pair;
+        }
+
+        // Pair 0 is reserved for the default colors
+        let pair = self.pairs.len() as i16 + 1;
+        if pair >= self.max_pairs {
+            return 0;
+        }
+
+        init_pair(pair, key.0, key.1);
+        self.pairs.insert(key, pair);
+        pair
+    }
+
+    /// Escape sequence that sets the same colors as the pair would
+    fn sgr(&self, fg: Option<Color>, bg: Option<Color>) -> String {
+        let code = |index: i16, base: i16| match index {
+            -1 => format!("{}", base + 9),
+            0..8 => format!("{}", base + index),
+            _ => format!("{};5;{index}", base + 8),
+        };
+
+        format!(
+            "\x1b[{};{}m",
+            code(self.index(fg), 30),
+            code(self.index(bg), 40)
+        )
+    }
+
+    fn index(&self, color: Option<Color>) -> i16 {
+        let Some(color) = color else {
+            // Terminal default
+            return -1;
+        };
+
+        let color = color.to_srgba();
+        if self.colors >= 256 {
+            // The 6x6x6 color cube of 256 color terminals
+            let level = |v: f32| (v.clamp(0.0, 1.0) * 5.0).round() as i16;
+            16 + 36 * level(color.red) + 6 * level(color.green) + level(color.blue)
+        } else {
+            // Red, green and blue are bits in the 8 basic colors
+            let bit = |v: f32, b: i16| if v > 0.5 { b } else { 0 };
+            bit(color.red, 1) | bit(color.green, 2) | bit(color.blue, 4)
+        }
+    }
+}
+
+impl Term {
+    /// Write escape sequences straight to the terminal, bypassing curses. Curses is flushed first,
+    /// and the cursor is restored afterwards so curses does not lose track of it.
+    pub(crate) fn write_raw(&self, bytes: &[u8]) {
+        self.window.refresh();
+
+        let mut stdout = std::io::stdout().lock();
+        if let Err(e) = stdout
+            .write_all(b"\x1b7")
+            .and_then(|_| stdout.write_all(bytes))
+            .and_then(|_| stdout.write_all(b"\x1b8"))
+            .and_then(|_| stdout.flush())
+        {
+            warn!("Failed writing to terminal: {}", e);
+        }
+    }
 }
 
 // SAFETY: Window cannot be passed between threads, but there is only ever 1 thread that uses it at
@@ -51,14 +497,26 @@ struct Term {
 unsafe impl Send for Term {}
 unsafe impl Sync for Term {}
 
+/// Escape sequences that curses cannot write, they are sent with the next frame
+#[derive(Resource, Default)]
+pub(crate) struct TermRawOutput(pub String);
+
+/// Keys read this frame. Focused text fields take the keys they use out of it, the rest are sent
+/// as [`TermInput`] events.
+#[derive(Resource, Default)]
+pub(crate) struct TermPendingInput(pub Vec<TermInput>);
+
 pub(crate) struct TermDrawPlugin {}
 
 impl Plugin for TermDrawPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<TermInput>()
             .add_event::<TermCommand>()
+            .init_resource::<TermCapabilities>()
+            .init_resource::<TermRawOutput>()
+            .init_resource::<TermPendingInput>()
             .add_systems(Startup, term_create)
-            .add_systems(PreUpdate, term_events)
+            .add_systems(PreUpdate, (term_events, term_send_input).chain())
             .add_systems(Last, (term_draw, term_commands.after(term_draw)));
     }
 }
@@ -76,13 +534,17 @@ fn term_create(mut commands: Commands) {
 
     // Create components
     commands.insert_resource(TermBuffer::new(window.get_max_x(), window.get_max_y()));
-    commands.insert_resource(Term { window });
+    commands.insert_resource(Term {
+        window,
+        colors: TermColors::new(),
+        cursor_visible: false,
+    });
 }
 
-fn term_events(
+pub(crate) fn term_events(
     terminal: Res<Term>,
     terminal_buffer: ResMut<TermBuffer>,
-    mut ev_input: EventWriter<TermInput>,
+    mut pending: ResMut<TermPendingInput>,
 ) {
     let mut resize = false;
 
@@ -92,7 +554,7 @@ fn term_events(
             Input::KeyResize => {
                 resize = true;
             }
-            _ => map_and_send_events(ev, &mut ev_input),
+            _ => pending.0.extend(map_input(ev)),
         }
     }
 
@@ -106,22 +568,107 @@ fn term_events(
     }
 }
 
-fn term_draw(terminal: Res<Term>, terminal_buffer: ResMut<TermBuffer>) {
+pub(crate) fn term_send_input(
+    mut pending: ResMut<TermPendingInput>,
+    mut ev_input: EventWriter<TermInput>,
+) {
+    ev_input.write_batch(pending.0.drain(..));
+}
+
+pub(crate) fn term_draw(
+    terminal: ResMut<Term>,
+    terminal_buffer: ResMut<TermBuffer>,
+    capabilities: Res<TermCapabilities>,
+    mut raw_output: ResMut<TermRawOutput>,
+) {
+    let terminal = terminal.into_inner();
     let terminal_buffer = terminal_buffer.into_inner();
+
+    // Curses cannot write hyperlinks, so linked cells are written again around it when they change
+    let links = match capabilities.hyperlinks {
+        true => terminal_buffer.link_runs(),
+        false => Vec::new(),
+    };
+    let mut out = String::new();
+    if links != terminal_buffer.links {
+        let sgr = |fg, bg| terminal.colors.as_ref().map(|colors| colors.sgr(fg, bg));
+
+        // Cells that are no longer linked are written again without the link
+        for run in terminal_buffer
+            .links
+            .iter()
+            .filter(|run| !links.contains(run))
+        {
+            out.push_str(&format!("\x1b[{};{}H", run.r + 1, run.c + 1));
+            let row = &terminal_buffer.buffer[run.r];
+            for v in row
+                .iter()
+                .skip(run.c)
+                .take(run.width)
+                .filter(|v| v.width > 0)
+            {
+                out.extend(sgr(v.cell.fg, v.cell.bg));
+                out.push_str(&v.text());
+            }
+        }
+
+        for run in links
+            .iter()
+            .filter(|run| !terminal_buffer.links.contains(run))
+        {
+            out.push_str(&format!("\x1b[{};{}H", run.r + 1, run.c + 1));
+            out.extend(sgr(run.fg, run.bg));
+            out.push_str(&format!(
+                "\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\",
+                run.url, run.text
+            ));
+        }
+        out.push_str("\x1b[0m");
+    }
+    out.push_str(&std::mem::take(&mut raw_output.0));
+
     for (r, v) in terminal_buffer.buffer.iter_mut().enumerate() {
         for (c, v) in v.iter_mut().enumerate() {
+            if v.width == 0 {
+                // The wide glyph before this one takes up this cell
+                *v = CLEAR;
+                continue;
+            }
+
+            if let Some(colors) = terminal.colors.as_mut() {
+                terminal.window.color_set(colors.pair(v.cell.fg, v.cell.bg));
+            }
+
             // Using the string method here to handle emojis
-            terminal
-                .window
-                .mvaddstr(r as i32, c as i32, format!("{}", v.0));
+            match &v.cluster {
+                Some(cluster) => terminal.window.mvaddstr(r as i32, c as i32, cluster),
+                None => terminal
+                    .window
+                    .mvaddstr(r as i32, c as i32, format!("{}", v.cell.glyph)),
+            };
 
             // Clear the char after write
             *v = CLEAR;
         }
     }
+
+    // Curses leaves the cursor where it was last moved to
+    let cursor = terminal_buffer.cursor.take();
+    if cursor.is_some() != terminal.cursor_visible {
+        terminal.cursor_visible = cursor.is_some();
+        curs_set(cursor.is_some() as i32);
+    }
+    if let Some((c, r)) = cursor {
+        terminal.window.mv(r as i32, c as i32);
+    }
+
+    terminal_buffer.links = links;
+    if !out.is_empty() {
+        terminal.write_raw(out.as_bytes());
+    }
 }
 
-fn term_commands(mut ev_cmd: EventReader<TermCommand>, mut exit: EventWriter<AppExit>) {
+pub(crate) fn term_commands(mut ev_cmd: EventReader<TermCommand>, mut exit: EventWriter<AppExit>) {
     if let Some(ev) = ev_cmd.read().next() {
         match ev {
             TermCommand::Exit => {
@@ -133,8 +680,8 @@ fn term_commands(mut ev_cmd: EventReader<TermCommand>, mut exit: EventWriter<App
     }
 }
 
-fn map_and_send_events(ev: Input, ev_input: &mut EventWriter<TermInput>) {
-    if let Some(ev) = match ev {
+fn map_input(ev: Input) -> Option<TermInput> {
+    match ev {
         Input::KeyBackspace => Some(TermInput::BackSpace),
         Input::Character(' ') => Some(TermInput::SpaceBar),
         Input::Character('\n') => Some(TermInput::Enter),
@@ -151,6 +698,12 @@ fn map_and_send_events(ev: Input, ev_input: &mut EventWriter<TermInput>) {
         Input::KeyDC => Some(TermInput::Delete),
         Input::KeyPPage => Some(TermInput::PageUp),
         Input::KeyNPage => Some(TermInput::PageDown),
+        Input::KeySLeft => Some(TermInput::ShiftLeft),
+        Input::KeySRight => Some(TermInput::ShiftRight),
+        Input::KeySR => Some(TermInput::ShiftUp),
+        Input::KeySF => Some(TermInput::ShiftDown),
+        Input::KeySHome => Some(TermInput::ShiftHome),
+        Input::KeySEnd => Some(TermInput::ShiftEnd),
         Input::KeyF1 => Some(TermInput::F1),
         Input::KeyF2 => Some(TermInput::F2),
         Input::KeyF3 => Some(TermInput::F3),
@@ -174,7 +727,5 @@ fn map_and_send_events(ev: Input, ev_input: &mut EventWriter<TermInput>) {
             warn!("Unknown input: {:?}", ev);
             None
         }
-    } {
-        ev_input.write(ev);
     }
 }
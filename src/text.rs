@@ -0,0 +1,332 @@
+use unicode_bidi::BidiInfo;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use bevy::prelude::*;
+
+use crate::prelude::{
+    TermCell, TermTextBounds, TermTextEffect, TermTextEffectKind, TermTextOverflow, TermTextWrap,
+};
+
+/// Split text into the lines that are drawn, wrapped and cut to the bounds if there are any
+pub(crate) fn layout(text: &str, bounds: Option<&TermTextBounds>) -> Vec<String> {
+    let Some(bounds) = bounds else {
+        return text.lines().map(str::to_string).collect();
+    };
+
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        match bounds.wrap {
+            TermTextWrap::Word => wrap_words(paragraph, bounds.width, &mut lines),
+            TermTextWrap::Char => wrap_chars(paragraph, bounds.width, &mut lines),
+            TermTextWrap::None => lines.push(paragraph.to_string()),
+        }
+    }
+
+    // Unwrapped lines, and glyphs wider than the bounds, can still be too long
+    for line in lines.iter_mut().filter(|line| line.width() > bounds.width) {
+        match bounds.overflow {
+            TermTextOverflow::Ellipsis => ellipsis(line, bounds.width),
+            _ => truncate(line, bounds.width),
+        }
+    }
+
+    if lines.len() <= bounds.height {
+        return lines;
+    }
+
+    match bounds.overflow {
+        TermTextOverflow::Clip => lines.truncate(bounds.height),
+        TermTextOverflow::Ellipsis => {
+            lines.truncate(bounds.height);
+            if let Some(last) = lines.last_mut() {
+                ellipsis(last, bounds.width);
+            }
+        }
+        TermTextOverflow::Scroll(offset) => {
+            let offset = offset.min(lines.len() - bounds.height);
+            lines.drain(..offset);
+            lines.truncate(bounds.height);
+        }
+    }
+    lines
+}
+
+fn wrap_words(paragraph: &str, width: usize, lines: &mut Vec<String>) {
+    let mut line = String::new();
+    let mut line_width = 0;
+    let mut space = String::new();
+
+    for (is_space, word) in words(paragraph) {
+        if is_space {
+            space.push_str(word);
+            continue;
+        }
+
+        let word_width = word.width();
+        if line_width > 0 && line_width + space.width() + word_width > width {
+            // Spaces where a line is broken are dropped
+            lines.push(std::mem::take(&mut line));
+            line_width = 0;
+        } else {
+            line_width += space.width();
+            line.push_str(&space);
+        }
+        space.clear();
+
+        if word_width > width {
+            for grapheme in word.graphemes(true) {
+                push_grapheme(grapheme, width, &mut line, &mut line_width, lines);
+            }
+        } else {
+            line.push_str(word);
+            line_width += word_width;
+        }
+    }
+
+    lines.push(line);
+}
+
+fn wrap_chars(paragraph: &str, width: usize, lines: &mut Vec<String>) {
+    let mut line = String::new();
+    let mut line_width = 0;
+
+    for grapheme in paragraph.graphemes(true) {
+        push_grapheme(grapheme, width, &mut line, &mut line_width, lines);
+    }
+
+    lines.push(line);
+}
+
+fn push_grapheme(
+    grapheme: &str,
+    width: usize,
+    line: &mut String,
+    line_width: &mut usize,
+    lines: &mut Vec<String>,
+) {
+    let grapheme_width = grapheme.width();
+    if *line_width > 0 && *line_width + grapheme_width > width {
+        lines.push(std::mem::take(line));
+        *line_width = 0;
+    }
+
+    line.push_str(grapheme);
+    *line_width += grapheme_width;
+}
+
+/// Runs of whitespace and of everything else, in order
+fn words(paragraph: &str) -> impl Iterator<Item = (bool, &str)> {
+    let mut rest = paragraph;
+    std::iter::from_fn(move || {
+        let first = rest.chars().next()?;
+        let is_space = first.is_whitespace();
+        let end = rest
+            .char_indices()
+            .find(|(_, c)| c.is_whitespace() != is_space)
+            .map_or(rest.len(), |(i, _)| i);
+
+        let (word, tail) = rest.split_at(end);
+        rest = tail;
+        Some((is_space, word))
+    })
+}
+
+/// Cut graphemes off the end of a line until it fits the width
+fn truncate(line: &mut String, width: usize) {
+    let mut line_width = line.width();
+    while line_width > width {
+        let Some((at, grapheme)) = line.grapheme_indices(true).next_back() else {
+            break;
+        };
+        line_width = line_width.saturating_sub(grapheme.width());
+        line.truncate(at);
+    }
+}
+
+/// Cut a line so it ends with '…' and still fits the width
+fn ellipsis(line: &mut String, width: usize) {
+    if width == 0 {
+        line.clear();
+        return;
+    }
+
+    truncate(line, width - 1);
+    line.push('…');
+}
+
+/// Put lines in the order they are seen in, so right to left scripts are drawn right to left.
+/// Each line gets its direction from its first strong char.
+pub(crate) fn reorder(lines: &mut [String]) {
+    for line in lines.iter_mut() {
+        if line.is_ascii() {
+            continue;
+        }
+
+        let bidi = BidiInfo::new(line, None);
+        if !bidi.has_rtl() {
+            continue;
+        }
+
+        let mut visual = String::with_capacity(line.len());
+        for paragraph in &bidi.paragraphs {
+            let (levels, runs) = bidi.visual_runs(paragraph, paragraph.range.clone());
+            for run in runs {
+                let text = &line[run.clone()];
+                if levels[run.start].is_rtl() {
+                    // Reverse whole clusters so marks stay on the chars they belong to
+                    text.graphemes(true).rev().for_each(|g| visual.push_str(g));
+                } else {
+                    visual.push_str(text);
+                }
+            }
+        }
+        *line = visual;
+    }
+}
+
+/// Apply an effect to laid out lines, returns the cell to draw them with or `None` if they are
+/// hidden right now
+pub(crate) fn apply_effect(
+    lines: &mut [String],
+    effect: Option<&TermTextEffect>,
+) -> Option<TermCell> {
+    let cell = TermCell::from(' ');
+    let Some(effect) = effect else {
+        return Some(cell);
+    };
+
+    let elapsed = effect.elapsed();
+    match effect.kind {
+        TermTextEffectKind::Typewriter { rate } => {
+            typewriter(lines, (elapsed * rate).max(0.0) as usize);
+            Some(cell)
+        }
+        TermTextEffectKind::Marquee { width, speed } => {
+            let offset = (elapsed * speed).max(0.0) as usize;
+            for line in lines.iter_mut() {
+                *line = marquee(line, width, offset);
+            }
+            Some(cell)
+        }
+        TermTextEffectKind::Blink { period } => {
+            (period <= 0.0 || elapsed % period < period / 2.0).then_some(cell)
+        }
+        TermTextEffectKind::Pulse { from, to, period } => {
+            let t = if period > 0.0 {
+                (1.0 - (elapsed / period * std::f32::consts::TAU).cos()) / 2.0
+            } else {
+                0.0
+            };
+            Some(cell.with_fg(from.mix(&to, t)))
+        }
+    }
+}
+
+/// How many grapheme clusters a typewriter effect has to reveal
+pub(crate) fn graphemes(lines: &[String]) -> usize {
+    lines.iter().map(|line| line.graphemes(true).count()).sum()
+}
+
+/// Keep the first `shown` grapheme clusters. The rest are blanked rather than removed, so aligned
+/// lines do not move while they are revealed.
+fn typewriter(lines: &mut [String], mut shown: usize) {
+    for line in lines.iter_mut() {
+        match line.grapheme_indices(true).nth(shown) {
+            Some((at, _)) => {
+                let hidden = line[at..].width();
+                line.truncate(at);
+                line.extend(std::iter::repeat_n(' ', hidden));
+                shown = 0;
+            }
+            None => shown -= line.graphemes(true).count(),
+        }
+    }
+}
+
+/// The part of a line that shows through a window `width` columns wide, when the line has
+/// scrolled `offset` columns. The line comes in from the right and leaves on the left.
+fn marquee(line: &str, width: usize, offset: usize) -> String {
+    let period = line.width() + width;
+    if period == 0 {
+        return String::new();
+    }
+
+    // The line starts just past the right edge of the window
+    let start = width as isize - (offset % period) as isize;
+    let mut window = String::with_capacity(width);
+    let mut col = start;
+    let mut filled = 0;
+
+    for grapheme in line.graphemes(true) {
+        let w = grapheme.width() as isize;
+        if col >= 0 && col + w <= width as isize {
+            window.extend(std::iter::repeat_n(
+                ' ',
+                (col as usize).saturating_sub(filled),
+            ));
+            window.push_str(grapheme);
+            filled = (col + w) as usize;
+        }
+        col += w;
+    }
+
+    window.extend(std::iter::repeat_n(' ', width.saturating_sub(filled)));
+    window
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bounds(width: usize, height: usize) -> TermTextBounds {
+        TermTextBounds::new(width, height)
+    }
+
+    #[test]
+    fn breaks_words_longer_than_the_width() {
+        let lines = layout("a extraordinary word", Some(&bounds(5, 10)));
+        assert_eq!(lines, ["a", "extra", "ordin", "ary", "word"]);
+
+        let char_wrap = bounds(5, 10).with_wrap(TermTextWrap::Char);
+        let lines = layout("a extraordinary", Some(&char_wrap));
+        assert_eq!(lines, ["a ext", "raord", "inary"]);
+    }
+
+    #[test]
+    fn keeps_wide_glyphs_whole_at_the_wrap() {
+        // The third glyph would take columns 4 and 5 of a 5 column line
+        let char_wrap = bounds(5, 10).with_wrap(TermTextWrap::Char);
+        let lines = layout("日本語です", Some(&char_wrap));
+        assert_eq!(lines, ["日本", "語で", "す"]);
+        assert!(lines.iter().all(|line| line.width() <= 5));
+
+        // A glyph wider than the bounds does not fit at all
+        let lines = layout("日本", Some(&bounds(1, 10)));
+        assert!(lines.iter().all(String::is_empty));
+    }
+
+    #[test]
+    fn draws_nothing_at_zero_width() {
+        for overflow in [TermTextOverflow::Clip, TermTextOverflow::Ellipsis] {
+            let lines = layout("some text", Some(&bounds(0, 1).with_overflow(overflow)));
+            assert!(lines.iter().all(String::is_empty), "{overflow:?}");
+        }
+    }
+
+    #[test]
+    fn ellipsis_marks_cut_text() {
+        let ellipsis = bounds(6, 2).with_overflow(TermTextOverflow::Ellipsis);
+        let lines = layout("one two three four", Some(&ellipsis));
+        assert_eq!(lines, ["one", "two…"]);
+
+        // Lines are cut sideways too when they are not wrapped
+        let unwrapped = ellipsis.with_wrap(TermTextWrap::None);
+        let lines = layout("a line too long\nshort", Some(&unwrapped));
+        assert_eq!(lines, ["a lin…", "short"]);
+
+        let clipped = unwrapped.with_overflow(TermTextOverflow::Clip);
+        let lines = layout("a line too long\nshort", Some(&clipped));
+        assert_eq!(lines, ["a line", "short"]);
+    }
+}